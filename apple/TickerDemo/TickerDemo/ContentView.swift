@@ -18,6 +18,7 @@ struct ContentView: View {
                             VStack(alignment: .trailing) {
                                 Text("$\(update.price, specifier: "%.2f")")
                                     .font(.title2)
+                                    .foregroundColor(color(for: update.direction))
                                 Text(formatTimestamp(update.timestampMs))
                                     .font(.caption)
                                     .foregroundColor(.secondary)
@@ -45,6 +46,17 @@ struct ContentView: View {
         .navigationTitle("Ticker Demo")
     }
 
+    private func color(for direction: TickDirection) -> Color {
+        switch direction {
+        case .up:
+            return .green
+        case .down:
+            return .red
+        case .unchanged:
+            return .primary
+        }
+    }
+
     private func formatTimestamp(_ ms: Int64) -> String {
         let date = Date(timeIntervalSince1970: Double(ms) / 1000.0)
         let formatter = DateFormatter()
@@ -1,11 +1,357 @@
+#[derive(uniffi::Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateKind {
+    Tick,
+    // Sent once when a symbol is halted; `price` is the last traded price.
+    Halt,
+    Resume,
+    // Cached last update replayed to a listener that subscribed mid-session.
+    // A halted symbol's snapshot keeps its halt `reason`.
+    Snapshot,
+    // Only from `read_cursor`: updates the cursor hadn't read were evicted
+    // here. Carries no symbol or price; `reason` gives the count.
+    Gap,
+}
+
+#[derive(uniffi::Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickDirection {
+    Up,
+    Down,
+    Unchanged,
+}
+
+impl TickDirection {
+    pub(crate) fn between(prev_price: f64, price: f64) -> Self {
+        if price > prev_price {
+            TickDirection::Up
+        } else if price < prev_price {
+            TickDirection::Down
+        } else {
+            TickDirection::Unchanged
+        }
+    }
+}
+
 #[derive(uniffi::Record, Debug, Clone)]
 pub struct PriceUpdate {
     pub symbol: String,
     pub price: f64,
     pub timestamp_ms: i64,
+    pub kind: UpdateKind,
+    pub reason: Option<String>,
+    // Relative to the last update actually delivered for this symbol, so
+    // conflated ticks never produce a flash the user didn't see.
+    pub direction: TickDirection,
+    pub prev_price: f64,
+    // Engine-wide and increasing in delivery order, so a listener sees each
+    // symbol's sequences strictly increase. Snapshots repeat the sequence of
+    // the update they copy. 0 means unsequenced (one-shot quotes, recordings
+    // from before sequences existed).
+    pub sequence: u64,
+}
+
+#[derive(uniffi::Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuePolicy {
+    DropOldest,
+    DropNewest,
+}
+
+#[derive(uniffi::Record, Debug, Clone)]
+pub struct TickerConfig {
+    #[uniffi(default = None)]
+    pub seed: Option<u64>,
+    #[uniffi(default = 0.02)]
+    pub spread: f64,
+    #[uniffi(default = 1)]
+    pub book_level_ticks: u32,
+    #[uniffi(default = false)]
+    pub emit_books: bool,
+    #[uniffi(default = 1000)]
+    pub history_capacity: u32,
+    #[uniffi(default = None)]
+    pub max_duration_ms: Option<u64>,
+    #[uniffi(default = None)]
+    pub max_ticks: Option<u64>,
+    // Opt-in watchdog: a price listener whose callback runs longer than this
+    // is dropped for good and reported as `EngineEvent::ListenerStalled`.
+    #[uniffi(default = None)]
+    pub listener_timeout_ms: Option<u64>,
+    // Caps the updates `drain_updates` has yet to read; `queue_policy` picks
+    // what goes when it is full. When `None`, only `retention_capacity`
+    // bounds it.
+    #[uniffi(default = None)]
+    pub queue_capacity: Option<u32>,
+    pub queue_policy: QueuePolicy,
+    // Caps every retained update, read by `drain_updates` or not. Past it the
+    // oldest go: lagging cursors read a `Gap`, and updates the drain hadn't
+    // read yet are lost to it as `EngineEvent::Backpressure`.
+    #[uniffi(default = 10000)]
+    pub retention_capacity: u32,
+    // Per-symbol rate limit; ticks arriving sooner are conflated into the next one.
+    #[uniffi(default = None)]
+    pub min_update_interval_ms: Option<u64>,
+    // When false, symbols in a running scenario move only by its steps.
+    #[uniffi(default = true)]
+    pub scenario_noise: bool,
+    // When true, `backfill` also fills a symbol's retained history while it
+    // is still empty, so stats windows have warm-up data.
+    #[uniffi(default = false)]
+    pub backfill_into_history: bool,
+}
+
+impl Default for TickerConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            spread: 0.02,
+            book_level_ticks: 1,
+            emit_books: false,
+            history_capacity: 1000,
+            max_duration_ms: None,
+            max_ticks: None,
+            listener_timeout_ms: None,
+            queue_capacity: None,
+            queue_policy: QueuePolicy::DropOldest,
+            retention_capacity: 10_000,
+            min_update_interval_ms: None,
+            scenario_noise: true,
+            backfill_into_history: false,
+        }
+    }
+}
+
+#[derive(uniffi::Enum, Debug, Clone, PartialEq)]
+pub enum SymbolKind {
+    Equity,
+    // FX pairs quote in fractional pips (one extra decimal beyond `pip_size`)
+    // and interpret `volatility`, `spread`, and the price floor in pips.
+    Fx { pip_size: f64, track_inverse: bool },
+}
+
+#[derive(uniffi::Record, Debug, Clone)]
+pub struct SymbolMetadata {
+    pub symbol: String,
+    pub kind: SymbolKind,
+    // Ignored for FX symbols, whose tick is derived from the pip size. Equity
+    // prices never fall below one tick. Ticks are rounded to 8 decimals, and
+    // anything finer than 1e-8 is clamped to it.
+    #[uniffi(default = 0.01)]
+    pub tick_size: f64,
+    #[uniffi(default = None)]
+    pub base_price: Option<f64>,
+    #[uniffi(default = None)]
+    pub volatility: Option<f64>,
+    #[uniffi(default = None)]
+    pub spread: Option<f64>,
+    // Crypto-style 24/7 symbol. Unset `volatility` and `spread` scale with
+    // `base_price` instead of assuming a ~100 equity, so 60,000 and 0.000012
+    // both move sensibly.
+    #[uniffi(default = false)]
+    pub continuous: bool,
+}
+
+#[uniffi::export]
+pub fn inverted_price(update: PriceUpdate) -> f64 {
+    if update.price > 0.0 {
+        1.0 / update.price
+    } else {
+        0.0
+    }
+}
+
+// Exactly one of `price` (absolute) or `delta_pct` (relative to the price
+// when the step fires) must be set. `at_ms` is an offset from scenario start.
+#[derive(uniffi::Record, Debug, Clone)]
+pub struct ScenarioStep {
+    pub at_ms: u64,
+    pub symbol: String,
+    #[uniffi(default = None)]
+    pub price: Option<f64>,
+    #[uniffi(default = None)]
+    pub delta_pct: Option<f64>,
+}
+
+#[derive(uniffi::Record, Debug, Clone)]
+pub struct WindowStats {
+    pub symbol: String,
+    pub window_ms: u64,
+    pub high: f64,
+    pub low: f64,
+    pub mean: f64,
+    // Standard deviation of tick-to-tick returns inside the window.
+    pub volatility: f64,
+    pub update_count: u32,
+    // False while retained history (or time since a reset) is shorter than the window.
+    pub complete: bool,
+}
+
+#[derive(uniffi::Record, Debug, Clone, PartialEq)]
+pub struct BookLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+#[derive(uniffi::Record, Debug, Clone)]
+pub struct OrderBook {
+    pub symbol: String,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+    pub timestamp_ms: i64,
+}
+
+#[derive(uniffi::Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+#[derive(uniffi::Record, Debug, Clone)]
+pub struct Fill {
+    pub order_id: u64,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: f64,
+    pub price: f64,
+    pub timestamp_ms: i64,
+}
+
+#[derive(uniffi::Record, Debug, Clone)]
+pub struct Position {
+    pub symbol: String,
+    pub quantity: f64,
+    pub average_price: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+}
+
+#[derive(uniffi::Error, Debug, Clone, PartialEq)]
+pub enum TickerError {
+    InvalidOrder { reason: String },
+    UnknownOrder { id: u64 },
+    UntrackedSymbol { symbol: String },
+    DecodeFailed { reason: String },
+    InvalidCorporateAction { reason: String },
+    QuoteUnavailable { symbol: String },
+    RuntimeUnavailable { reason: String },
+    Io { reason: String },
+    InvalidReplaySpeed { speed: f64 },
+    InvalidPrice { price: f64 },
+    InvalidScenario { reason: String },
+    ScenarioActive,
+    InvalidConfig { field: String, reason: String },
+    ConfigFieldImmutable { field: String },
+    UnknownCursor { id: u64 },
+}
+
+impl std::fmt::Display for TickerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TickerError::InvalidOrder { reason } => write!(f, "invalid order: {reason}"),
+            TickerError::UnknownOrder { id } => write!(f, "unknown order id {id}"),
+            TickerError::UntrackedSymbol { symbol } => write!(f, "{symbol} is not being tracked"),
+            TickerError::DecodeFailed { reason } => write!(f, "failed to decode updates: {reason}"),
+            TickerError::InvalidCorporateAction { reason } => {
+                write!(f, "invalid corporate action: {reason}")
+            }
+            TickerError::QuoteUnavailable { symbol } => {
+                write!(f, "no quote available for {symbol}")
+            }
+            TickerError::RuntimeUnavailable { reason } => {
+                write!(f, "tokio runtime unavailable: {reason}")
+            }
+            TickerError::Io { reason } => write!(f, "io error: {reason}"),
+            TickerError::InvalidReplaySpeed { speed } => {
+                write!(f, "replay speed must be positive, got {speed}")
+            }
+            TickerError::InvalidPrice { price } => write!(f, "price must be positive, got {price}"),
+            TickerError::InvalidScenario { reason } => write!(f, "invalid scenario: {reason}"),
+            TickerError::ScenarioActive => write!(f, "a scenario is already running"),
+            TickerError::InvalidConfig { field, reason } => {
+                write!(f, "invalid config field {field}: {reason}")
+            }
+            TickerError::ConfigFieldImmutable { field } => {
+                write!(f, "config field {field} can't change while tracking")
+            }
+            TickerError::UnknownCursor { id } => write!(f, "unknown cursor id {id}"),
+        }
+    }
+}
+
+impl std::error::Error for TickerError {}
+
+#[derive(uniffi::Enum, Debug, Clone, PartialEq)]
+pub enum CorporateActionKind {
+    Split { ratio: f64 },
+    Dividend { amount: f64 },
+}
+
+#[derive(uniffi::Record, Debug, Clone)]
+pub struct CorporateAction {
+    pub symbol: String,
+    pub kind: CorporateActionKind,
+    pub scheduled_ms: i64,
+    pub applied_ms: i64,
+    pub price_before: f64,
+    pub price_after: f64,
+}
+
+#[derive(uniffi::Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Cancelled,
+    LimitReached,
+}
+
+// Events for one engine are delivered to every event listener in the order
+// they occurred, on a dedicated dispatch thread. Only `Error` can arrive
+// before `Started` (e.g. the tick runtime failed to launch, in which case
+// `Started` never follows); everything else is emitted by a running loop.
+#[derive(uniffi::Enum, Debug, Clone)]
+pub enum EngineEvent {
+    Started { symbols: Vec<String> },
+    Stopped { reason: StopReason },
+    Halted { symbol: String, reason: String },
+    Resumed { symbol: String },
+    Fill { fill: Fill },
+    CorporateAction { action: CorporateAction },
+    Book { symbol: String, book: OrderBook },
+    Error { message: String },
+    // A price listener's callback ran past `listener_timeout_ms`; it has been
+    // removed and gets no further updates unless added again.
+    ListenerStalled { id: u64, duration_ms: u64 },
+    // `queue_capacity` or `retention_capacity` was reached, so `dropped`
+    // updates will never reach `drain_updates`; `pending` are still unread.
+    Backpressure { dropped: u32, pending: u32 },
 }
 
 #[uniffi::export]
 pub trait PriceListener: Send + Sync {
     fn on_price(&self, update: PriceUpdate);
 }
+
+// Only invoked when `TickerConfig::emit_books` is set; building and bridging
+// ten levels per symbol per tick is noticeably heavier than a price update.
+// Book and fill listeners are adapters over the `EngineEvent` stream.
+#[uniffi::export]
+pub trait BookListener: Send + Sync {
+    fn on_book(&self, symbol: String, book: OrderBook);
+}
+
+#[uniffi::export]
+pub trait FillListener: Send + Sync {
+    fn on_fill(&self, fill: Fill);
+}
+
+// External quote provider (e.g. an HTTP client on the Swift side). Called
+// from a blocking worker thread by `fetch_quote` and `backfill`, so
+// implementations may block.
+#[uniffi::export]
+pub trait PriceSource: Send + Sync {
+    fn fetch_price(&self, symbol: String) -> Option<f64>;
+    // Up to `points` historical prices, oldest first, spaced `interval_ms`
+    // apart and ending now. `None` lets the engine synthesize them.
+    fn fetch_history(&self, symbol: String, points: u32, interval_ms: u64) -> Option<Vec<f64>>;
+}
+
+#[uniffi::export]
+pub trait EventListener: Send + Sync {
+    fn on_event(&self, event: EngineEvent);
+}
@@ -0,0 +1,104 @@
+use crate::api::{BookLevel, OrderBook};
+use crate::pricing::ticks_to_price;
+use rand::Rng;
+
+pub(crate) const BOOK_DEPTH: usize = 5;
+
+const BASE_LEVEL_SIZE: f64 = 100.0;
+
+pub(crate) fn build_book<R: Rng>(
+    symbol: &str,
+    mid: f64,
+    tick: f64,
+    spread: f64,
+    level_ticks: u32,
+    timestamp_ms: i64,
+    rng: &mut R,
+) -> OrderBook {
+    let half_spread = spread.max(0.0) / 2.0;
+    let best_bid = ((mid - half_spread) / tick).floor() as i64;
+    let mut best_ask = ((mid + half_spread) / tick).ceil() as i64;
+    if best_ask <= best_bid {
+        best_ask = best_bid + 1;
+    }
+    let step = i64::from(level_ticks.max(1));
+
+    let bids = (0..BOOK_DEPTH as i64)
+        .map(|level| best_bid - level * step)
+        .take_while(|&ticks| ticks > 0)
+        .enumerate()
+        .map(|(level, ticks)| book_level(ticks, tick, level, rng))
+        .collect();
+    let asks = (0..BOOK_DEPTH as i64)
+        .map(|level| best_ask + level * step)
+        .enumerate()
+        .map(|(level, ticks)| book_level(ticks, tick, level, rng))
+        .collect();
+
+    OrderBook {
+        symbol: symbol.to_string(),
+        bids,
+        asks,
+        timestamp_ms,
+    }
+}
+
+// Deeper levels carry more size on average, with noise so the book visibly moves.
+fn book_level<R: Rng>(ticks: i64, tick: f64, level: usize, rng: &mut R) -> BookLevel {
+    let base = BASE_LEVEL_SIZE * (1.0 + level as f64 * 0.5);
+    BookLevel {
+        price: ticks_to_price(ticks, tick),
+        size: (base * rng.gen_range(0.5..1.5)).round().max(1.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn on_tick(price: f64, tick: f64) -> bool {
+        price == ticks_to_price((price / tick).round() as i64, tick)
+    }
+
+    #[test]
+    fn levels_are_ordered_uncrossed_and_on_tick() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for tick in [1.0, 0.25, 0.05, 0.01, 0.0001, 1e-8] {
+            for _ in 0..500 {
+                let mid = rng.gen_range(tick..tick * 1e6);
+                // Down to a fraction of a tick, where both sides meet at the mid.
+                let spread = tick * rng.gen_range(0.0..10.0);
+                let level_ticks = rng.gen_range(1..4);
+                let book = build_book("SYM", mid, tick, spread, level_ticks, 0, &mut rng);
+                let context = format!("mid {mid} tick {tick} spread {spread}");
+
+                let bids: Vec<f64> = book.bids.iter().map(|level| level.price).collect();
+                let asks: Vec<f64> = book.asks.iter().map(|level| level.price).collect();
+                assert_eq!(asks.len(), BOOK_DEPTH, "{context}");
+                assert!(
+                    bids.windows(2).all(|pair| pair[0] > pair[1]),
+                    "{context}: {bids:?}"
+                );
+                assert!(
+                    asks.windows(2).all(|pair| pair[0] < pair[1]),
+                    "{context}: {asks:?}"
+                );
+                assert!(bids.iter().all(|&price| price > 0.0), "{context}: {bids:?}");
+                if let Some(&best_bid) = bids.first() {
+                    assert!(best_bid < asks[0], "{context}: {best_bid} / {}", asks[0]);
+                }
+                assert!(
+                    bids.iter().chain(&asks).all(|&price| on_tick(price, tick)),
+                    "{context}: {bids:?} {asks:?}"
+                );
+                assert!(book
+                    .bids
+                    .iter()
+                    .chain(&book.asks)
+                    .all(|level| level.size >= 1.0));
+            }
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use crate::api::{PriceUpdate, TickDirection, TickerError, UpdateKind};
+
+// Framed little-endian batch format:
+//   version: u8 | count: varint | count * record
+//   record: symbol: str | price: f64 | timestamp_ms: i64 | kind: u8 | reason: option<str>
+//           | direction: u8 | prev_price: f64   (version 2 and later)
+//           | sequence: varint                    (version 3 and later)
+//   str: len varint + UTF-8 bytes, option: 0u8 | 1u8 + value
+// Version 1 buffers (e.g. older recordings) still decode, as unchanged ticks;
+// versions before 3 decode as unsequenced.
+const BINARY_VERSION: u8 = 3;
+
+#[derive(uniffi::Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeFormat {
+    Binary,
+}
+
+pub(crate) fn encode_updates(updates: &[PriceUpdate], format: EncodeFormat) -> Vec<u8> {
+    match format {
+        EncodeFormat::Binary => encode_binary(updates),
+    }
+}
+
+#[uniffi::export]
+pub fn decode_updates(data: Vec<u8>) -> Result<Vec<PriceUpdate>, TickerError> {
+    let mut reader = Reader {
+        data: &data,
+        pos: 0,
+    };
+    let version = reader.u8()?;
+    if version == 0 || version > BINARY_VERSION {
+        return Err(decode_error(format!("unsupported version {version}")));
+    }
+    let count = reader.varint()?;
+    // Every record is at least 19 bytes, which bounds the allocation for
+    // hostile counts.
+    let mut updates = Vec::with_capacity(count.min(data.len() as u64 / 19) as usize);
+    for _ in 0..count {
+        updates.push(reader.update(version)?);
+    }
+    if reader.pos != data.len() {
+        return Err(decode_error(format!(
+            "{} trailing bytes",
+            data.len() - reader.pos
+        )));
+    }
+    Ok(updates)
+}
+
+fn encode_binary(updates: &[PriceUpdate]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + updates.len() * 24);
+    out.push(BINARY_VERSION);
+    put_varint(&mut out, updates.len() as u64);
+    for update in updates {
+        put_str(&mut out, &update.symbol);
+        out.extend_from_slice(&update.price.to_le_bytes());
+        out.extend_from_slice(&update.timestamp_ms.to_le_bytes());
+        out.push(kind_tag(update.kind));
+        match &update.reason {
+            Some(reason) => {
+                out.push(1);
+                put_str(&mut out, reason);
+            }
+            None => out.push(0),
+        }
+        out.push(direction_tag(update.direction));
+        out.extend_from_slice(&update.prev_price.to_le_bytes());
+        put_varint(&mut out, update.sequence);
+    }
+    out
+}
+
+fn direction_tag(direction: TickDirection) -> u8 {
+    match direction {
+        TickDirection::Unchanged => 0,
+        TickDirection::Up => 1,
+        TickDirection::Down => 2,
+    }
+}
+
+fn kind_tag(kind: UpdateKind) -> u8 {
+    match kind {
+        UpdateKind::Tick => 0,
+        UpdateKind::Halt => 1,
+        UpdateKind::Resume => 2,
+        UpdateKind::Snapshot => 3,
+        UpdateKind::Gap => 4,
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn put_str(out: &mut Vec<u8>, value: &str) {
+    put_varint(out, value.len() as u64);
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn decode_error(reason: String) -> TickerError {
+    TickerError::DecodeFailed { reason }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], TickerError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| decode_error(format!("truncated at byte {}", self.pos)))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, TickerError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], TickerError> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.take(N)?);
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64, TickerError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(decode_error("varint overflows u64".to_string()))
+    }
+
+    fn string(&mut self) -> Result<String, TickerError> {
+        let len = usize::try_from(self.varint()?)
+            .map_err(|_| decode_error("string length overflows usize".to_string()))?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|err| decode_error(err.to_string()))
+    }
+
+    fn update(&mut self, version: u8) -> Result<PriceUpdate, TickerError> {
+        let symbol = self.string()?;
+        let price = f64::from_le_bytes(self.array()?);
+        let timestamp_ms = i64::from_le_bytes(self.array()?);
+        let kind = match self.u8()? {
+            0 => UpdateKind::Tick,
+            1 => UpdateKind::Halt,
+            2 => UpdateKind::Resume,
+            3 => UpdateKind::Snapshot,
+            4 => UpdateKind::Gap,
+            tag => return Err(decode_error(format!("unknown update kind {tag}"))),
+        };
+        let reason = match self.u8()? {
+            0 => None,
+            1 => Some(self.string()?),
+            tag => return Err(decode_error(format!("invalid option tag {tag}"))),
+        };
+        let (direction, prev_price) = if version >= 2 {
+            let direction = match self.u8()? {
+                0 => TickDirection::Unchanged,
+                1 => TickDirection::Up,
+                2 => TickDirection::Down,
+                tag => return Err(decode_error(format!("unknown tick direction {tag}"))),
+            };
+            (direction, f64::from_le_bytes(self.array()?))
+        } else {
+            (TickDirection::Unchanged, price)
+        };
+        let sequence = if version >= 3 { self.varint()? } else { 0 };
+        Ok(PriceUpdate {
+            symbol,
+            price,
+            timestamp_ms,
+            kind,
+            reason,
+            direction,
+            prev_price,
+            sequence,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(symbol: &str, price: f64, sequence: u64) -> PriceUpdate {
+        PriceUpdate {
+            symbol: symbol.to_string(),
+            price,
+            timestamp_ms: 1_700_000_000_000,
+            kind: UpdateKind::Tick,
+            reason: None,
+            direction: TickDirection::Up,
+            prev_price: price - 0.01,
+            sequence,
+        }
+    }
+
+    fn sample() -> Vec<PriceUpdate> {
+        vec![
+            update("AAPL", 187.42, 1),
+            PriceUpdate {
+                kind: UpdateKind::Halt,
+                reason: Some("volatility pause ⏸".to_string()),
+                direction: TickDirection::Unchanged,
+                ..update("EUR/USD", 1.08345, 2)
+            },
+            PriceUpdate {
+                price: f64::NAN,
+                timestamp_ms: -42,
+                kind: UpdateKind::Snapshot,
+                direction: TickDirection::Down,
+                prev_price: -3.5,
+                ..update("", 0.0, u64::MAX)
+            },
+            PriceUpdate {
+                kind: UpdateKind::Gap,
+                ..update("MSFT", 410.0, 300)
+            },
+        ]
+    }
+
+    fn assert_same(left: &[PriceUpdate], right: &[PriceUpdate]) {
+        assert_eq!(left.len(), right.len());
+        for (left, right) in left.iter().zip(right) {
+            assert_eq!(left.symbol, right.symbol);
+            assert_eq!(left.price.to_bits(), right.price.to_bits());
+            assert_eq!(left.timestamp_ms, right.timestamp_ms);
+            assert_eq!(left.kind, right.kind);
+            assert_eq!(left.reason, right.reason);
+            assert_eq!(left.direction, right.direction);
+            assert_eq!(left.prev_price.to_bits(), right.prev_price.to_bits());
+            assert_eq!(left.sequence, right.sequence);
+        }
+    }
+
+    #[test]
+    fn round_trips_every_field() {
+        let updates = sample();
+        let decoded = decode_updates(encode_updates(&updates, EncodeFormat::Binary)).unwrap();
+        assert_same(&updates, &decoded);
+        assert!(decode_updates(encode_updates(&[], EncodeFormat::Binary))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn every_truncation_is_an_error() {
+        let encoded = encode_updates(&sample(), EncodeFormat::Binary);
+        for len in 0..encoded.len() {
+            assert!(
+                decode_updates(encoded[..len].to_vec()).is_err(),
+                "decoded a buffer truncated to {len} bytes"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_bytes_and_unknown_versions() {
+        let mut encoded = encode_updates(&sample(), EncodeFormat::Binary);
+        encoded.push(0);
+        assert!(decode_updates(encoded.clone()).is_err());
+        encoded[0] = BINARY_VERSION + 1;
+        assert!(decode_updates(encoded).is_err());
+        assert!(decode_updates(vec![0, 0]).is_err());
+    }
+
+    // Hand-built buffers in the older layouts, as older recordings hold them.
+    fn legacy_record(out: &mut Vec<u8>, version: u8) {
+        put_str(out, "AAPL");
+        out.extend_from_slice(&101.5f64.to_le_bytes());
+        out.extend_from_slice(&7i64.to_le_bytes());
+        out.push(1);
+        out.push(1);
+        put_str(out, "halted");
+        if version >= 2 {
+            out.push(2);
+            out.extend_from_slice(&102.0f64.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn decodes_version_1_and_2_buffers() {
+        for version in [1, 2] {
+            let mut buffer = vec![version];
+            put_varint(&mut buffer, 1);
+            legacy_record(&mut buffer, version);
+
+            let decoded = decode_updates(buffer).unwrap();
+            assert_eq!(decoded.len(), 1);
+            let update = &decoded[0];
+            assert_eq!(update.symbol, "AAPL");
+            assert_eq!(update.price, 101.5);
+            assert_eq!(update.timestamp_ms, 7);
+            assert_eq!(update.kind, UpdateKind::Halt);
+            assert_eq!(update.reason.as_deref(), Some("halted"));
+            assert_eq!(update.sequence, 0);
+            if version == 1 {
+                assert_eq!(update.direction, TickDirection::Unchanged);
+                assert_eq!(update.prev_price, 101.5);
+            } else {
+                assert_eq!(update.direction, TickDirection::Down);
+                assert_eq!(update.prev_price, 102.0);
+            }
+        }
+    }
+
+    // Roughly what a Swift `Codable` encoder emits for the same records.
+    fn json(updates: &[PriceUpdate]) -> String {
+        let records: Vec<String> = updates
+            .iter()
+            .map(|update| {
+                format!(
+                    "{{\"symbol\":\"{}\",\"price\":{},\"timestampMs\":{},\"kind\":\"tick\",\
+                     \"reason\":null,\"direction\":\"up\",\"prevPrice\":{},\"sequence\":{}}}",
+                    update.symbol,
+                    update.price,
+                    update.timestamp_ms,
+                    update.prev_price,
+                    update.sequence
+                )
+            })
+            .collect();
+        format!("[{}]", records.join(","))
+    }
+
+    #[test]
+    fn binary_is_much_smaller_than_json() {
+        let updates: Vec<PriceUpdate> = (0..500)
+            .map(|index| update("AAPL", 150.0 + index as f64 * 0.01, index + 1))
+            .collect();
+        let binary = encode_updates(&updates, EncodeFormat::Binary).len();
+        let json = json(&updates).len();
+        assert!(
+            binary * 3 < json,
+            "binary {binary} bytes vs json {json} bytes"
+        );
+    }
+}
@@ -0,0 +1,422 @@
+use crate::api::{PriceUpdate, QueuePolicy, TickDirection, UpdateKind};
+use crate::engine::current_timestamp_ms;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// `drain_updates` reads through this cursor. It never gets gap markers, so
+// single-consumer callers see the same stream the old drain queue gave them.
+pub(crate) const DRAIN_CURSOR: u64 = 0;
+
+struct Cursor {
+    name: String,
+    // Sequence of the next update this cursor wants.
+    position: u64,
+}
+
+// Delivered updates in sequence order, shared by every pull consumer. An
+// update is retained until all cursors have read past it or a cap evicts it.
+// The queue capacity only counts what `drain_updates` has yet to read, so a
+// slow cursor can't stall the drain; the retention cap bounds everything
+// held, so neither can an unused drain or an abandoned cursor pin memory.
+// Sequences are gapless as delivered, so a cursor finds what it missed by
+// spotting holes rather than by bookkeeping at eviction time.
+pub(crate) struct UpdateBuffer {
+    updates: VecDeque<PriceUpdate>,
+    cursors: HashMap<u64, Cursor>,
+    next_cursor_id: u64,
+    last_sequence: u64,
+}
+
+impl UpdateBuffer {
+    pub(crate) fn new() -> Self {
+        let mut cursors = HashMap::new();
+        cursors.insert(
+            DRAIN_CURSOR,
+            Cursor {
+                name: String::new(),
+                position: 1,
+            },
+        );
+        Self {
+            updates: VecDeque::new(),
+            cursors,
+            next_cursor_id: DRAIN_CURSOR + 1,
+            last_sequence: 0,
+        }
+    }
+
+    // A full queue sheds non-priority updates before priority ones, so the
+    // symbols on screen survive backpressure under either policy. Returns how
+    // many updates `drain_updates` lost, the incoming one included.
+    pub(crate) fn push(
+        &mut self,
+        update: PriceUpdate,
+        capacity: Option<u32>,
+        policy: QueuePolicy,
+        retention: u32,
+        priority: &HashSet<String>,
+    ) -> u32 {
+        self.last_sequence = self.last_sequence.max(update.sequence);
+        let incoming_priority = priority.contains(&update.symbol);
+        let mut dropped = 0;
+        if let Some(capacity) = capacity.map(|capacity| capacity.max(1) as usize) {
+            while self.pending() as usize >= capacity {
+                if policy == QueuePolicy::DropNewest && !incoming_priority {
+                    return dropped + 1;
+                }
+                // Only a priority update may push out another priority
+                // update; anything else is dropped.
+                if !self.evict_unread(priority, incoming_priority) {
+                    return dropped + 1;
+                }
+                dropped += 1;
+            }
+        }
+        self.updates.push_back(update);
+        self.trim();
+
+        // A hard cap on memory, whether or not `drain_updates` is used: the
+        // oldest go regardless of priority, since cursors may still want
+        // anything newer.
+        while self.updates.len() > retention.max(1) as usize {
+            if self.drain_start() == 0 {
+                dropped += 1;
+            }
+            self.updates.pop_front();
+        }
+        dropped
+    }
+
+    // Removes the oldest update `drain_updates` hasn't read that isn't a
+    // priority symbol's, or failing that and if allowed, the oldest one.
+    fn evict_unread(&mut self, priority: &HashSet<String>, allow_priority: bool) -> bool {
+        let unread = self.drain_start();
+        let victim = self
+            .updates
+            .range(unread..)
+            .position(|retained| !priority.contains(&retained.symbol))
+            .map(|victim| unread + victim)
+            .or(allow_priority.then_some(unread));
+        victim
+            .and_then(|victim| self.updates.remove(victim))
+            .is_some()
+    }
+
+    // Reusing a name returns the existing cursor so a reconnecting consumer
+    // resumes where it left off. New cursors start after the newest update.
+    pub(crate) fn create(&mut self, name: String) -> u64 {
+        if let Some((&id, _)) = self
+            .cursors
+            .iter()
+            .find(|(&id, cursor)| id != DRAIN_CURSOR && cursor.name == name)
+        {
+            return id;
+        }
+        let id = self.next_cursor_id;
+        self.next_cursor_id += 1;
+        self.cursors.insert(
+            id,
+            Cursor {
+                name,
+                position: self.last_sequence + 1,
+            },
+        );
+        id
+    }
+
+    // Returns up to `max` updates, plus a `Gap` marker wherever updates this
+    // cursor hadn't read were evicted. Markers don't count toward `max`.
+    pub(crate) fn read(&mut self, id: u64, max: u32) -> Option<Vec<PriceUpdate>> {
+        let mut expected = self.cursors.get(&id)?.position;
+        let start = self
+            .updates
+            .partition_point(|update| update.sequence < expected);
+
+        let mut read = Vec::new();
+        for update in self.updates.range(start..).take(max as usize) {
+            if id != DRAIN_CURSOR && update.sequence > expected {
+                read.push(gap_marker(update.sequence - expected));
+            }
+            read.push(update.clone());
+            expected = update.sequence + 1;
+        }
+
+        if let Some(cursor) = self.cursors.get_mut(&id) {
+            cursor.position = expected;
+        }
+        self.trim();
+        Some(read)
+    }
+
+    // Rewinding past the oldest retained update yields a gap marker for the
+    // part that is gone, ahead of the next update read.
+    pub(crate) fn reset(&mut self, id: u64, to_sequence: u64) -> bool {
+        let Some(cursor) = self.cursors.get_mut(&id) else {
+            return false;
+        };
+        cursor.position = to_sequence.max(1);
+        self.trim();
+        true
+    }
+
+    pub(crate) fn remove(&mut self, id: u64) -> bool {
+        let removed = id != DRAIN_CURSOR && self.cursors.remove(&id).is_some();
+        self.trim();
+        removed
+    }
+
+    // Updates `drain_updates` has yet to read.
+    pub(crate) fn pending(&self) -> u32 {
+        (self.updates.len() - self.drain_start()) as u32
+    }
+
+    // Index of the first update `drain_updates` hasn't read; everything
+    // before it is only kept for lagging cursors.
+    fn drain_start(&self) -> usize {
+        let position = self.cursors[&DRAIN_CURSOR].position;
+        self.updates
+            .partition_point(|update| update.sequence < position)
+    }
+
+    fn trim(&mut self) {
+        let oldest_wanted = self
+            .cursors
+            .values()
+            .map(|cursor| cursor.position)
+            .min()
+            .unwrap_or(u64::MAX);
+        while self
+            .updates
+            .front()
+            .is_some_and(|update| update.sequence < oldest_wanted)
+        {
+            self.updates.pop_front();
+        }
+    }
+}
+
+fn gap_marker(missed: u64) -> PriceUpdate {
+    PriceUpdate {
+        symbol: String::new(),
+        price: 0.0,
+        timestamp_ms: current_timestamp_ms(),
+        kind: UpdateKind::Gap,
+        reason: Some(format!("missed {missed} updates")),
+        direction: TickDirection::Unchanged,
+        prev_price: 0.0,
+        sequence: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(symbol: &str, sequence: u64) -> PriceUpdate {
+        PriceUpdate {
+            symbol: symbol.to_string(),
+            price: 100.0,
+            timestamp_ms: sequence as i64,
+            kind: UpdateKind::Tick,
+            reason: None,
+            direction: TickDirection::Unchanged,
+            prev_price: 100.0,
+            sequence,
+        }
+    }
+
+    fn priority(symbols: &[&str]) -> HashSet<String> {
+        symbols.iter().map(|symbol| symbol.to_string()).collect()
+    }
+
+    fn drained(buffer: &mut UpdateBuffer) -> Vec<(String, u64)> {
+        buffer
+            .read(DRAIN_CURSOR, u32::MAX)
+            .unwrap()
+            .into_iter()
+            .map(|update| (update.symbol, update.sequence))
+            .collect()
+    }
+
+    fn labels(expected: &[(&str, u64)]) -> Vec<(String, u64)> {
+        expected
+            .iter()
+            .map(|(symbol, sequence)| (symbol.to_string(), *sequence))
+            .collect()
+    }
+
+    #[test]
+    fn priority_updates_survive_a_full_queue_under_both_policies() {
+        for policy in [QueuePolicy::DropOldest, QueuePolicy::DropNewest] {
+            let mut buffer = UpdateBuffer::new();
+            let priority = priority(&["AAPL"]);
+            let symbols = ["AAPL", "MSFT", "AAPL", "MSFT", "MSFT", "AAPL", "MSFT"];
+            for (index, symbol) in symbols.iter().enumerate() {
+                buffer.push(
+                    update(symbol, index as u64 + 1),
+                    Some(3),
+                    policy,
+                    10,
+                    &priority,
+                );
+            }
+            let kept = drained(&mut buffer);
+            let aapl: Vec<u64> = kept
+                .iter()
+                .filter(|(symbol, _)| symbol == "AAPL")
+                .map(|(_, sequence)| *sequence)
+                .collect();
+            assert_eq!(aapl, vec![1, 3, 6], "{policy:?} evicted priority: {kept:?}");
+        }
+    }
+
+    #[test]
+    fn non_priority_update_is_dropped_when_only_priority_is_retained() {
+        let mut buffer = UpdateBuffer::new();
+        let priority = priority(&["AAPL"]);
+        buffer.push(
+            update("AAPL", 1),
+            Some(2),
+            QueuePolicy::DropOldest,
+            10,
+            &priority,
+        );
+        buffer.push(
+            update("AAPL", 2),
+            Some(2),
+            QueuePolicy::DropOldest,
+            10,
+            &priority,
+        );
+        buffer.push(
+            update("MSFT", 3),
+            Some(2),
+            QueuePolicy::DropOldest,
+            10,
+            &priority,
+        );
+        assert_eq!(drained(&mut buffer), labels(&[("AAPL", 1), ("AAPL", 2)]));
+    }
+
+    #[test]
+    fn priority_update_displaces_the_oldest_priority_when_full_of_priority() {
+        let mut buffer = UpdateBuffer::new();
+        let priority = priority(&["AAPL"]);
+        for sequence in 1..=3 {
+            buffer.push(
+                update("AAPL", sequence),
+                Some(2),
+                QueuePolicy::DropNewest,
+                10,
+                &priority,
+            );
+        }
+        assert_eq!(drained(&mut buffer), labels(&[("AAPL", 2), ("AAPL", 3)]));
+    }
+
+    #[test]
+    fn lagging_cursor_does_not_stall_the_drain() {
+        let mut buffer = UpdateBuffer::new();
+        let lagging = buffer.create("lagging".to_string());
+        let priority = priority(&[]);
+        for sequence in 1..=4 {
+            buffer.push(
+                update("MSFT", sequence),
+                Some(2),
+                QueuePolicy::DropNewest,
+                2,
+                &priority,
+            );
+            if sequence % 2 == 0 {
+                assert_eq!(
+                    drained(&mut buffer),
+                    labels(&[("MSFT", sequence - 1), ("MSFT", sequence)])
+                );
+            }
+        }
+
+        let read = buffer.read(lagging, u32::MAX).unwrap();
+        assert_eq!(read[0].kind, UpdateKind::Gap);
+        assert_eq!(read[0].reason.as_deref(), Some("missed 2 updates"));
+        let sequences: Vec<u64> = read[1..].iter().map(|update| update.sequence).collect();
+        assert_eq!(sequences, vec![3, 4]);
+    }
+
+    #[test]
+    fn retention_bounds_an_abandoned_cursor_without_a_queue_cap() {
+        let mut buffer = UpdateBuffer::new();
+        buffer.create("abandoned".to_string());
+        let priority = priority(&[]);
+        for sequence in 1..=1000 {
+            buffer.push(
+                update("MSFT", sequence),
+                None,
+                QueuePolicy::DropOldest,
+                10,
+                &priority,
+            );
+            assert_eq!(drained(&mut buffer), labels(&[("MSFT", sequence)]));
+            assert!(
+                buffer.updates.len() <= 10,
+                "{} retained",
+                buffer.updates.len()
+            );
+        }
+    }
+
+    #[test]
+    fn push_reports_what_each_policy_shed() {
+        for policy in [QueuePolicy::DropOldest, QueuePolicy::DropNewest] {
+            let mut buffer = UpdateBuffer::new();
+            let priority = priority(&[]);
+            let shed: Vec<u32> = (1..=4)
+                .map(|sequence| {
+                    buffer.push(update("MSFT", sequence), Some(2), policy, 10, &priority)
+                })
+                .collect();
+            assert_eq!(shed, vec![0, 0, 1, 1], "{policy:?}");
+            assert_eq!(drained(&mut buffer).len(), 2);
+            assert_eq!(
+                buffer.push(update("MSFT", 5), Some(2), policy, 10, &priority),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn retention_bounds_named_cursors_when_nothing_drains() {
+        let mut buffer = UpdateBuffer::new();
+        let cursors = [
+            buffer.create("app".to_string()),
+            buffer.create("widget".to_string()),
+        ];
+        let priority = priority(&["AAPL"]);
+        let mut dropped = 0;
+        for sequence in 1..=5000 {
+            let symbol = if sequence % 100 == 0 { "AAPL" } else { "MSFT" };
+            dropped += buffer.push(
+                update(symbol, sequence),
+                None,
+                QueuePolicy::DropOldest,
+                10,
+                &priority,
+            );
+            for &cursor in &cursors {
+                let read = buffer.read(cursor, u32::MAX).unwrap();
+                assert_eq!(read.len(), 1, "named cursors keep up without gaps");
+            }
+            assert!(
+                buffer.updates.len() <= 10,
+                "{} retained",
+                buffer.updates.len()
+            );
+        }
+        assert_eq!(dropped, 4990);
+
+        // The unused drain is left with the newest updates.
+        let kept: Vec<u64> = drained(&mut buffer)
+            .into_iter()
+            .map(|(_, sequence)| sequence)
+            .collect();
+        assert_eq!(kept, (4991..=5000).collect::<Vec<u64>>());
+    }
+}
@@ -1,17 +1,83 @@
-use crate::api::{PriceListener, PriceUpdate};
-use rand::Rng;
-use std::collections::{HashMap, VecDeque};
+use crate::api::{
+    BookListener, CorporateAction, CorporateActionKind, EngineEvent, EventListener, FillListener,
+    OrderBook, OrderSide, Position, PriceListener, PriceSource, PriceUpdate, ScenarioStep,
+    StopReason, SymbolKind, SymbolMetadata, TickDirection, TickerConfig, TickerError, UpdateKind,
+    WindowStats,
+};
+use crate::book::build_book;
+use crate::codec::{encode_updates, EncodeFormat};
+use crate::cursors::{UpdateBuffer, DRAIN_CURSOR};
+use crate::events::{BookAdapter, EventBus, FillAdapter};
+use crate::listeners::ListenerEntry;
+use crate::pricing::{default_metadata, derived_inverse_metadata, inverse_symbol, PriceModel};
+use crate::recording::{read_recording, Recorder};
+use crate::scenario::Scenario;
+use crate::simulator::{initial_price, inverse_leg, SimCore, TICK_INTERVAL_MS};
+use crate::stats::StatsWindow;
+use crate::trading::{apply_split_to_orders, match_orders, Order, Portfolio};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc, Mutex,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex, MutexGuard, OnceLock,
 };
+use std::time::Instant;
 use tokio::runtime::Runtime;
 use tokio::time::{sleep, Duration};
 
 struct EngineState {
     cancel: AtomicBool,
     running: AtomicBool,
-    queue: Mutex<VecDeque<PriceUpdate>>,
+    updates: Mutex<UpdateBuffer>,
+    listeners: Mutex<Vec<ListenerEntry>>,
+    next_listener_id: AtomicU64,
+    next_sequence: AtomicU64,
+    config: Mutex<TickerConfig>,
+    metadata: Mutex<HashMap<String, SymbolMetadata>>,
+    books: Mutex<HashMap<String, OrderBook>>,
+    events: EventBus,
+    book_listener: Mutex<Option<u64>>,
+    latest: Mutex<HashMap<String, PriceUpdate>>,
+    history: Mutex<HashMap<String, VecDeque<PriceUpdate>>>,
+    next_window_id: AtomicU64,
+    stats_windows: Mutex<HashMap<u64, StatsWindow>>,
+    next_order_id: AtomicU64,
+    orders: Mutex<Vec<Order>>,
+    portfolio: Mutex<Portfolio>,
+    fill_listener: Mutex<Option<u64>>,
+    tracked: Mutex<HashSet<String>>,
+    halted: Mutex<HashMap<String, String>>,
+    halt_changes: Mutex<VecDeque<HaltChange>>,
+    corporate_actions: Mutex<Vec<ScheduledAction>>,
+    price_source: Mutex<Option<Arc<dyn PriceSource>>>,
+    recorder: Mutex<Option<Recorder>>,
+    priority: Mutex<HashSet<String>>,
+    price_overrides: Mutex<HashMap<String, f64>>,
+    // Last backfilled price of symbols outside the running session; the next
+    // session starts them there so the chart joins up with the live ticks.
+    backfill_anchors: Mutex<HashMap<String, f64>>,
+    scenario: Mutex<Option<Scenario>>,
+}
+
+struct Quote {
+    symbol: String,
+    price: f64,
+    model: PriceModel,
+    timestamp_ms: i64,
+    inverse: Option<(String, f64, PriceModel)>,
+}
+
+struct ScheduledAction {
+    symbol: String,
+    kind: CorporateActionKind,
+    at_ms: i64,
+}
+
+// Halts and resumes are announced by the tick loop, in the order they were requested.
+enum HaltChange {
+    Halt { symbol: String, reason: String },
+    Resume { symbol: String },
 }
 
 #[derive(uniffi::Object)]
@@ -23,13 +89,382 @@ pub struct TickerEngine {
 impl TickerEngine {
     #[uniffi::constructor]
     pub fn new() -> Arc<Self> {
-        Arc::new(Self {
-            state: Arc::new(EngineState {
-                cancel: AtomicBool::new(false),
-                running: AtomicBool::new(false),
-                queue: Mutex::new(VecDeque::new()),
-            }),
-        })
+        Self::build(TickerConfig::default())
+    }
+
+    // Rejects the same records `apply_config` does.
+    #[uniffi::constructor]
+    pub fn with_config(config: TickerConfig) -> Result<Arc<Self>, TickerError> {
+        validate_config(&config)?;
+        Ok(Self::build(config))
+    }
+
+    pub fn register_symbol(&self, metadata: SymbolMetadata) {
+        lock(&self.state.metadata).insert(metadata.symbol.clone(), metadata);
+    }
+
+    pub fn format_price(&self, symbol: String, price: f64) -> String {
+        let config = lock(&self.state.config).clone();
+        PriceModel::resolve(&self.state.display_metadata(&symbol, price), &config).format(price)
+    }
+
+    // `symbols: None` subscribes to every symbol. Subscribing while tracking
+    // is active first delivers the cached last update of each matching symbol
+    // as `UpdateKind::Snapshot`; snapshots never enter the drain queue.
+    pub fn add_listener(
+        &self,
+        listener: Arc<dyn PriceListener>,
+        symbols: Option<Vec<String>>,
+    ) -> u64 {
+        let id = self.state.next_listener_id.fetch_add(1, Ordering::SeqCst);
+        let entry = ListenerEntry::new(id, listener, symbols);
+
+        let mut listeners = lock(&self.state.listeners);
+        if self.state.running.load(Ordering::SeqCst) {
+            let mut snapshot: Vec<PriceUpdate> = lock(&self.state.latest)
+                .values()
+                .filter(|update| entry.wants(&update.symbol))
+                .cloned()
+                .collect();
+            snapshot.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+            for mut update in snapshot {
+                update.kind = UpdateKind::Snapshot;
+                entry.deliver(update);
+            }
+        }
+        listeners.push(entry);
+        id
+    }
+
+    pub fn remove_listener(&self, id: u64) {
+        lock(&self.state.listeners).retain(|entry| entry.id != id);
+    }
+
+    pub fn add_event_listener(&self, listener: Arc<dyn EventListener>) -> u64 {
+        self.state.events.add_listener(listener)
+    }
+
+    pub fn remove_event_listener(&self, id: u64) {
+        self.state.events.remove_listener(id);
+    }
+
+    pub fn set_book_listener(&self, listener: Option<Arc<dyn BookListener>>) {
+        let adapter = listener.map(|listener| Arc::new(BookAdapter(listener)) as _);
+        self.state
+            .replace_adapter(&self.state.book_listener, adapter);
+    }
+
+    pub fn get_order_book(&self, symbol: String) -> Option<OrderBook> {
+        lock(&self.state.books).get(&symbol).cloned()
+    }
+
+    pub fn set_fill_listener(&self, listener: Option<Arc<dyn FillListener>>) {
+        let adapter = listener.map(|listener| Arc::new(FillAdapter(listener)) as _);
+        self.state
+            .replace_adapter(&self.state.fill_listener, adapter);
+    }
+
+    pub fn submit_order(
+        &self,
+        symbol: String,
+        side: OrderSide,
+        quantity: f64,
+        limit_price: Option<f64>,
+    ) -> Result<u64, TickerError> {
+        if !quantity.is_finite() || quantity <= 0.0 {
+            return Err(TickerError::InvalidOrder {
+                reason: format!("quantity must be positive, got {quantity}"),
+            });
+        }
+        if let Some(limit) = limit_price {
+            if !limit.is_finite() || limit <= 0.0 {
+                return Err(TickerError::InvalidOrder {
+                    reason: format!("limit price must be positive, got {limit}"),
+                });
+            }
+        }
+
+        let id = self.state.next_order_id.fetch_add(1, Ordering::SeqCst);
+        lock(&self.state.orders).push(Order {
+            id,
+            symbol,
+            side,
+            quantity,
+            limit_price,
+        });
+        Ok(id)
+    }
+
+    pub fn cancel_order(&self, id: u64) -> Result<(), TickerError> {
+        let mut orders = lock(&self.state.orders);
+        match orders.iter().position(|order| order.id == id) {
+            Some(index) => {
+                orders.remove(index);
+                Ok(())
+            }
+            None => Err(TickerError::UnknownOrder { id }),
+        }
+    }
+
+    pub fn get_positions(&self) -> Vec<Position> {
+        let marks: HashMap<String, f64> = lock(&self.state.latest)
+            .iter()
+            .map(|(symbol, update)| (symbol.clone(), update.price))
+            .collect();
+        lock(&self.state.portfolio).positions(&marks)
+    }
+
+    pub fn halt_symbol(&self, symbol: String, reason: String) -> Result<(), TickerError> {
+        self.state.ensure_tracked(&symbol)?;
+        let mut halted = lock(&self.state.halted);
+        if halted.contains_key(&symbol) {
+            return Ok(());
+        }
+        halted.insert(symbol.clone(), reason.clone());
+        lock(&self.state.halt_changes).push_back(HaltChange::Halt { symbol, reason });
+        Ok(())
+    }
+
+    pub fn resume_symbol(&self, symbol: String) -> Result<(), TickerError> {
+        self.state.ensure_tracked(&symbol)?;
+        let mut halted = lock(&self.state.halted);
+        if halted.remove(&symbol).is_some() {
+            lock(&self.state.halt_changes).push_back(HaltChange::Resume { symbol });
+        }
+        Ok(())
+    }
+
+    pub fn schedule_split(
+        &self,
+        symbol: String,
+        ratio: f64,
+        at_ms: i64,
+    ) -> Result<(), TickerError> {
+        if !ratio.is_finite() || ratio <= 0.0 {
+            return Err(TickerError::InvalidCorporateAction {
+                reason: format!("split ratio must be positive, got {ratio}"),
+            });
+        }
+        self.state
+            .schedule_action(symbol, CorporateActionKind::Split { ratio }, at_ms);
+        Ok(())
+    }
+
+    pub fn schedule_dividend(
+        &self,
+        symbol: String,
+        amount: f64,
+        at_ms: i64,
+    ) -> Result<(), TickerError> {
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(TickerError::InvalidCorporateAction {
+                reason: format!("dividend amount must be positive, got {amount}"),
+            });
+        }
+        self.state
+            .schedule_action(symbol, CorporateActionKind::Dividend { amount }, at_ms);
+        Ok(())
+    }
+
+    pub fn latest_prices(&self) -> Vec<PriceUpdate> {
+        let mut prices: Vec<PriceUpdate> = lock(&self.state.latest).values().cloned().collect();
+        prices.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        prices
+    }
+
+    // Updates with `from_ms <= timestamp_ms < to_ms`, oldest first. Ranges
+    // reaching past the retained history return what is still buffered;
+    // compare against `oldest_retained_ms` to detect truncation.
+    pub fn get_updates_between(
+        &self,
+        symbol: String,
+        from_ms: i64,
+        to_ms: i64,
+    ) -> Vec<PriceUpdate> {
+        if from_ms >= to_ms {
+            return Vec::new();
+        }
+        let history = lock(&self.state.history);
+        let Some(updates) = history.get(&symbol) else {
+            return Vec::new();
+        };
+        let start = updates.partition_point(|update| update.timestamp_ms < from_ms);
+        let end = updates.partition_point(|update| update.timestamp_ms < to_ms);
+        updates.range(start..end).cloned().collect()
+    }
+
+    pub fn oldest_retained_ms(&self, symbol: String) -> Option<i64> {
+        lock(&self.state.history)
+            .get(&symbol)
+            .and_then(|updates| updates.front())
+            .map(|update| update.timestamp_ms)
+    }
+
+    // Synthetic history for charts, oldest first, `interval_ms` apart and
+    // ending now at the symbol's current price: the last delivered one, or
+    // what `fetch_quote` would return. A configured `PriceSource` can supply
+    // it instead. Seeded engines produce the same path for the same price.
+    // Backfilled points are never delivered, queued, or recorded. A symbol
+    // that isn't being tracked starts its next session at the last point.
+    pub async fn backfill(
+        &self,
+        symbol: String,
+        points: u32,
+        interval_ms: u64,
+    ) -> Result<Vec<PriceUpdate>, TickerError> {
+        let runtime =
+            shared_runtime().map_err(|reason| TickerError::RuntimeUnavailable { reason })?;
+        let state = self.state.clone();
+        runtime
+            .spawn_blocking(move || state.backfill(symbol, points, interval_ms))
+            .await
+            .map_err(|err| TickerError::RuntimeUnavailable {
+                reason: err.to_string(),
+            })?
+    }
+
+    // Windows can span more than `history_capacity` retains; they then report
+    // what is buffered with `complete: false`.
+    pub fn add_stats_window(&self, symbol: String, window_ms: u64) -> u64 {
+        let id = self.state.next_window_id.fetch_add(1, Ordering::SeqCst);
+        lock(&self.state.stats_windows).insert(
+            id,
+            StatsWindow {
+                symbol,
+                window_ms,
+                reset_ms: i64::MIN,
+            },
+        );
+        id
+    }
+
+    pub fn get_window_stats(&self, id: u64) -> Option<WindowStats> {
+        let windows = lock(&self.state.stats_windows);
+        let window = windows.get(&id)?;
+        let history = lock(&self.state.history);
+        Some(window.compute(history.get(&window.symbol), current_timestamp_ms()))
+    }
+
+    pub fn reset_stats_window(&self, id: u64) {
+        if let Some(window) = lock(&self.state.stats_windows).get_mut(&id) {
+            window.reset_ms = current_timestamp_ms();
+        }
+    }
+
+    pub fn remove_stats_window(&self, id: u64) {
+        lock(&self.state.stats_windows).remove(&id);
+    }
+
+    pub fn set_price_source(&self, source: Option<Arc<dyn PriceSource>>) {
+        *lock(&self.state.price_source) = source;
+    }
+
+    // One-off quote that bypasses listeners, the drain queue, and history.
+    // Each call runs on its own blocking worker of the shared runtime, so
+    // lookups for different symbols proceed in parallel.
+    pub async fn fetch_quote(&self, symbol: String) -> Result<PriceUpdate, TickerError> {
+        let runtime =
+            shared_runtime().map_err(|reason| TickerError::RuntimeUnavailable { reason })?;
+        let state = self.state.clone();
+        runtime
+            .spawn_blocking(move || state.quote(symbol))
+            .await
+            .map_err(|err| TickerError::RuntimeUnavailable {
+                reason: err.to_string(),
+            })?
+    }
+
+    // Appends every emitted update (not snapshots) to `path`, flushing every
+    // `flush_every` records. A write failure is reported once through
+    // `EngineEvent::Error` and turns recording off; ticking carries on.
+    pub fn start_recording_to_file(
+        &self,
+        path: String,
+        flush_every: u32,
+    ) -> Result<(), TickerError> {
+        let recorder = Recorder::open(&path, flush_every)?;
+        if let Some(mut previous) = lock(&self.state.recorder).replace(recorder) {
+            let _ = previous.flush();
+        }
+        Ok(())
+    }
+
+    pub fn stop_recording_file(&self) -> Result<(), TickerError> {
+        match lock(&self.state.recorder).take() {
+            Some(mut recorder) => recorder.flush(),
+            None => Ok(()),
+        }
+    }
+
+    // Plays a recording to `listener` with its original spacing divided by
+    // `speed`. The file is read up front, so IO errors are returned here.
+    pub fn replay_file(
+        &self,
+        path: String,
+        speed: f64,
+        listener: Arc<dyn PriceListener>,
+    ) -> Result<(), TickerError> {
+        if !speed.is_finite() || speed <= 0.0 {
+            return Err(TickerError::InvalidReplaySpeed { speed });
+        }
+        let updates = read_recording(&path)?;
+        let runtime =
+            shared_runtime().map_err(|reason| TickerError::RuntimeUnavailable { reason })?;
+        let id = self.state.next_listener_id.fetch_add(1, Ordering::SeqCst);
+        let entry = ListenerEntry::new(id, listener, None);
+
+        runtime.spawn(async move {
+            let mut previous_ms: Option<i64> = None;
+            for update in updates {
+                if let Some(previous_ms) = previous_ms {
+                    let gap_ms = (update.timestamp_ms - previous_ms).max(0) as f64 / speed;
+                    sleep(Duration::from_millis(gap_ms as u64)).await;
+                }
+                previous_ms = Some(update.timestamp_ms);
+                entry.deliver(update);
+            }
+        });
+        Ok(())
+    }
+
+    // Priority symbols bypass `min_update_interval_ms`, are the last to be
+    // evicted from a full queue, and are delivered first within a tick. An
+    // empty list restores uniform treatment.
+    pub fn set_priority_symbols(&self, symbols: Vec<String>) {
+        *lock(&self.state.priority) = symbols.into_iter().collect();
+    }
+
+    // Pins `symbol` to `price` on its next tick, which then carries no noise.
+    pub fn set_price(&self, symbol: String, price: f64) -> Result<(), TickerError> {
+        if !price.is_finite() || price <= 0.0 {
+            return Err(TickerError::InvalidPrice { price });
+        }
+        self.state.ensure_tracked(&symbol)?;
+        lock(&self.state.price_overrides).insert(symbol, price);
+        Ok(())
+    }
+
+    // Steps fire on the first tick at or after their offset and then flow
+    // through the normal pipeline (listeners, queue, history, orders). Steps
+    // that come due while their symbol is halted are skipped.
+    pub fn run_scenario(&self, steps: Vec<ScenarioStep>) -> Result<(), TickerError> {
+        let scenario = Scenario::new(steps)?;
+        for symbol in scenario.symbols() {
+            self.state.ensure_tracked(symbol)?;
+        }
+        let mut active = lock(&self.state.scenario);
+        if active
+            .as_ref()
+            .is_some_and(|running| !running.is_finished())
+        {
+            return Err(TickerError::ScenarioActive);
+        }
+        *active = Some(scenario);
+        Ok(())
+    }
+
+    pub fn cancel_scenario(&self) {
+        lock(&self.state.scenario).take();
     }
 
     pub fn start_tracking(&self, symbols: Vec<String>, listener: Arc<dyn PriceListener>) {
@@ -41,82 +476,1169 @@ impl TickerEngine {
             return;
         }
 
+        let runtime = match shared_runtime() {
+            Ok(runtime) => runtime,
+            Err(err) => {
+                eprintln!("Failed to start tokio runtime: {err}");
+                self.state.events.emit(EngineEvent::Error {
+                    message: format!("failed to start tokio runtime: {err}"),
+                });
+                self.state.running.store(false, Ordering::SeqCst);
+                return;
+            }
+        };
+
         self.state.cancel.store(false, Ordering::SeqCst);
+        *lock(&self.state.tracked) = symbols.iter().cloned().collect();
+        lock(&self.state.latest).clear();
+        let anchors = std::mem::take(&mut *lock(&self.state.backfill_anchors));
+        let session_listener = self.add_listener(listener, None);
 
         let state = self.state.clone();
-        std::thread::spawn(move || {
-            let runtime = match Runtime::new() {
-                Ok(runtime) => runtime,
-                Err(err) => {
-                    eprintln!("Failed to start tokio runtime: {err}");
-                    state.running.store(false, Ordering::SeqCst);
-                    return;
-                }
+        runtime.spawn(async move {
+            let watchdog = tokio::spawn(watch_listeners(state.clone()));
+            state.events.emit(EngineEvent::Started {
+                symbols: symbols.clone(),
+            });
+            let started_at = Instant::now();
+            let config = lock(&state.config).clone();
+            let mut sim = SimCore::new(config.seed);
+            // Books draw from their own generator so the walk matches a
+            // `PriceSimulator` with the same seed.
+            let mut book_rng = match config.seed {
+                Some(seed) => StdRng::seed_from_u64(seed ^ BOOK_SEED_SALT),
+                None => StdRng::from_entropy(),
             };
+            for symbol in &symbols {
+                let metadata = state.symbol_metadata(symbol);
+                let base_price = anchors.get(symbol).copied().or(metadata.base_price);
+                sim.add_symbol(
+                    &SymbolMetadata {
+                        base_price,
+                        ..metadata
+                    },
+                    &config,
+                );
+            }
+
+            let mut ticks: u64 = 0;
+            let mut last_emitted: HashMap<String, Instant> = HashMap::new();
+            let reason = loop {
+                if state.cancel.load(Ordering::SeqCst) {
+                    break StopReason::Cancelled;
+                }
+                // Limits are re-read every tick so runtime setters take effect
+                // on the next pass, even if they are already exceeded.
+                let config = lock(&state.config).clone();
+                let elapsed_ms = started_at.elapsed().as_millis() as u64;
+                let duration_reached = config.max_duration_ms.is_some_and(|max| elapsed_ms >= max);
+                let ticks_reached = config.max_ticks.is_some_and(|max| ticks >= max);
+                if duration_reached || ticks_reached {
+                    break StopReason::LimitReached;
+                }
+                ticks += 1;
+
+                state.announce_halt_changes(&sim, &config);
 
-            runtime.block_on(async move {
-                let mut rng = rand::thread_rng();
-                let mut prices: HashMap<String, f64> = symbols
-                    .into_iter()
-                    .map(|symbol| {
-                        let base = rng.gen_range(90.0..110.0);
-                        (symbol, base)
-                    })
-                    .collect();
-
-                while !state.cancel.load(Ordering::SeqCst) {
-                    for (symbol, price) in prices.iter_mut() {
-                        let delta = rng.gen_range(-1.0..1.0);
-                        *price = (*price + delta).max(0.01);
-
-                        let update = PriceUpdate {
-                            symbol: symbol.clone(),
-                            price: *price,
-                            timestamp_ms: current_timestamp_ms(),
-                        };
-
-                        listener.on_price(update.clone());
-
-                        if let Ok(mut queue) = state.queue.lock() {
-                            queue.push_back(update);
+                // Walk every symbol first so the RNG sequence doesn't depend on
+                // which symbols are currently prioritized.
+                let mut quotes: Vec<Quote> = Vec::new();
+                for symbol in sim.symbols() {
+                    if lock(&state.halted).contains_key(&symbol) {
+                        state.skip_scripted_moves(&symbol);
+                        continue;
+                    }
+
+                    let metadata = state.symbol_metadata(&symbol);
+                    let model = PriceModel::resolve(&metadata, &config);
+                    let timestamp_ms = current_timestamp_ms();
+                    let scripted = match sim.price_mut(&symbol) {
+                        Some(price) => {
+                            state.apply_corporate_actions(&symbol, price, &model, timestamp_ms);
+                            state.apply_scripted_moves(&symbol, price, config.scenario_noise)
                         }
+                        None => continue,
+                    };
+                    let noise_scale = if scripted { 0.0 } else { 1.0 };
+                    let Some(price) = sim.walk(&symbol, &model, noise_scale) else {
+                        continue;
+                    };
+
+                    // The inverse leg is rate limited with its direct pair.
+                    let inverse = inverse_leg(&metadata, price, &config, |inverse| {
+                        state.inverse_metadata(inverse, &metadata, price)
+                    });
+
+                    quotes.push(Quote {
+                        symbol,
+                        price,
+                        model,
+                        timestamp_ms,
+                        inverse,
+                    });
+                }
+
+                // Priority symbols go out first and skip the rate limiter.
+                let priority = lock(&state.priority).clone();
+                quotes.sort_by_key(|quote| !priority.contains(&quote.symbol));
+                for quote in quotes {
+                    let is_priority = priority.contains(&quote.symbol);
+                    let conflated = !is_priority
+                        && config.min_update_interval_ms.is_some_and(|min_ms| {
+                            last_emitted
+                                .get(&quote.symbol)
+                                .is_some_and(|emitted: &Instant| {
+                                    emitted.elapsed() < Duration::from_millis(min_ms)
+                                })
+                        });
+                    if !conflated {
+                        last_emitted.insert(quote.symbol.clone(), Instant::now());
                     }
 
-                    sleep(Duration::from_millis(500)).await;
+                    state.publish(
+                        &quote.symbol,
+                        quote.price,
+                        &quote.model,
+                        quote.timestamp_ms,
+                        &config,
+                        conflated,
+                        &mut book_rng,
+                    );
+                    if let Some((inverse, inverse_price, inverse_model)) = quote.inverse {
+                        state.publish(
+                            &inverse,
+                            inverse_price,
+                            &inverse_model,
+                            quote.timestamp_ms,
+                            &config,
+                            conflated,
+                            &mut book_rng,
+                        );
+                    }
                 }
 
-                state.running.store(false, Ordering::SeqCst);
-                println!("TickerEngine stopped");
-            });
+                sleep(Duration::from_millis(TICK_INTERVAL_MS)).await;
+            };
+
+            watchdog.abort();
+            lock(&state.listeners).retain(|entry| entry.id != session_listener);
+            lock(&state.tracked).clear();
+            lock(&state.halted).clear();
+            lock(&state.halt_changes).clear();
+            lock(&state.price_overrides).clear();
+            lock(&state.scenario).take();
+            // `Stopped` is queued while this session still owns `running`, so
+            // a restart can't get its `Started` onto the bus ahead of it.
+            state.events.emit(EngineEvent::Stopped { reason });
+            state.running.store(false, Ordering::SeqCst);
+            println!("TickerEngine stopped");
         });
     }
 
+    pub fn set_max_duration_ms(&self, max_duration_ms: Option<u64>) {
+        lock(&self.state.config).max_duration_ms = max_duration_ms;
+    }
+
+    pub fn set_max_ticks(&self, max_ticks: Option<u64>) {
+        lock(&self.state.config).max_ticks = max_ticks;
+    }
+
+    pub fn current_config(&self) -> TickerConfig {
+        lock(&self.state.config).clone()
+    }
+
+    // Validates the whole record and replaces the config in one step. The
+    // tick loop reads the config once per tick, so each tick runs entirely on
+    // the old or the new config.
+    pub fn apply_config(&self, config: TickerConfig) -> Result<(), TickerError> {
+        validate_config(&config)?;
+        let mut current = lock(&self.state.config);
+        // The walk's generator is seeded once per session.
+        if self.state.running.load(Ordering::SeqCst) && config.seed != current.seed {
+            return Err(TickerError::ConfigFieldImmutable {
+                field: "seed".to_string(),
+            });
+        }
+        *current = config;
+        Ok(())
+    }
+
     pub fn cancel(&self) {
         self.state.cancel.store(true, Ordering::SeqCst);
     }
 
     pub fn drain_updates(&self, max: u32) -> Vec<PriceUpdate> {
-        let mut updates = Vec::new();
-        let mut queue = match self.state.queue.lock() {
-            Ok(queue) => queue,
-            Err(poisoned) => poisoned.into_inner(),
+        lock(&self.state.updates)
+            .read(DRAIN_CURSOR, max)
+            .unwrap_or_default()
+    }
+
+    pub fn drain_updates_encoded(&self, max: u32, format: EncodeFormat) -> Vec<u8> {
+        encode_updates(&self.drain_updates(max), format)
+    }
+
+    // Independent pull consumers over the same retained updates; reading one
+    // cursor never consumes updates for another or for `drain_updates`, whose
+    // own cursor isn't reachable through these calls.
+    pub fn create_cursor(&self, name: String) -> u64 {
+        lock(&self.state.updates).create(name)
+    }
+
+    pub fn read_cursor(&self, id: u64, max: u32) -> Result<Vec<PriceUpdate>, TickerError> {
+        if id == DRAIN_CURSOR {
+            return Err(TickerError::UnknownCursor { id });
+        }
+        lock(&self.state.updates)
+            .read(id, max)
+            .ok_or(TickerError::UnknownCursor { id })
+    }
+
+    // The next read starts at the first retained update at or after
+    // `to_sequence`.
+    pub fn reset_cursor(&self, id: u64, to_sequence: u64) -> Result<(), TickerError> {
+        if id != DRAIN_CURSOR && lock(&self.state.updates).reset(id, to_sequence) {
+            Ok(())
+        } else {
+            Err(TickerError::UnknownCursor { id })
+        }
+    }
+
+    pub fn drop_cursor(&self, id: u64) -> Result<(), TickerError> {
+        if lock(&self.state.updates).remove(id) {
+            Ok(())
+        } else {
+            Err(TickerError::UnknownCursor { id })
+        }
+    }
+}
+
+impl TickerEngine {
+    fn build(config: TickerConfig) -> Arc<Self> {
+        Arc::new(Self {
+            state: Arc::new(EngineState {
+                cancel: AtomicBool::new(false),
+                running: AtomicBool::new(false),
+                updates: Mutex::new(UpdateBuffer::new()),
+                listeners: Mutex::new(Vec::new()),
+                next_listener_id: AtomicU64::new(1),
+                next_sequence: AtomicU64::new(1),
+                config: Mutex::new(config),
+                metadata: Mutex::new(HashMap::new()),
+                books: Mutex::new(HashMap::new()),
+                events: EventBus::new(),
+                book_listener: Mutex::new(None),
+                latest: Mutex::new(HashMap::new()),
+                history: Mutex::new(HashMap::new()),
+                next_window_id: AtomicU64::new(1),
+                stats_windows: Mutex::new(HashMap::new()),
+                next_order_id: AtomicU64::new(1),
+                orders: Mutex::new(Vec::new()),
+                portfolio: Mutex::new(Portfolio::default()),
+                fill_listener: Mutex::new(None),
+                tracked: Mutex::new(HashSet::new()),
+                halted: Mutex::new(HashMap::new()),
+                halt_changes: Mutex::new(VecDeque::new()),
+                corporate_actions: Mutex::new(Vec::new()),
+                price_source: Mutex::new(None),
+                recorder: Mutex::new(None),
+                priority: Mutex::new(HashSet::new()),
+                price_overrides: Mutex::new(HashMap::new()),
+                backfill_anchors: Mutex::new(HashMap::new()),
+                scenario: Mutex::new(None),
+            }),
+        })
+    }
+}
+
+impl EngineState {
+    fn replace_adapter(&self, slot: &Mutex<Option<u64>>, adapter: Option<Arc<dyn EventListener>>) {
+        let mut slot = lock(slot);
+        if let Some(id) = slot.take() {
+            self.events.remove_listener(id);
+        }
+        *slot = adapter.map(|adapter| self.events.add_listener(adapter));
+    }
+
+    fn ensure_tracked(&self, symbol: &str) -> Result<(), TickerError> {
+        if lock(&self.tracked).contains(symbol) {
+            Ok(())
+        } else {
+            Err(TickerError::UntrackedSymbol {
+                symbol: symbol.to_string(),
+            })
+        }
+    }
+
+    fn quote(&self, symbol: String) -> Result<PriceUpdate, TickerError> {
+        let config = lock(&self.config).clone();
+        let metadata = self.symbol_metadata(&symbol);
+        let model = PriceModel::resolve(&metadata, &config);
+
+        let source = lock(&self.price_source).clone();
+        let price = match source {
+            Some(source) => source
+                .fetch_price(symbol.clone())
+                .filter(|price| price.is_finite() && *price > 0.0)
+                .ok_or_else(|| TickerError::QuoteUnavailable {
+                    symbol: symbol.clone(),
+                })?,
+            // Seeded engines quote the same price for a symbol every time.
+            None => {
+                let mut rng = match config.seed {
+                    Some(seed) => StdRng::seed_from_u64(seed ^ symbol_hash(&symbol)),
+                    None => StdRng::from_entropy(),
+                };
+                initial_price(&metadata, &mut rng)
+            }
+        };
+
+        let price = model.round(price.max(model.floor));
+        Ok(PriceUpdate {
+            symbol,
+            price,
+            timestamp_ms: current_timestamp_ms(),
+            kind: UpdateKind::Tick,
+            reason: None,
+            direction: TickDirection::Unchanged,
+            prev_price: price,
+            sequence: 0,
+        })
+    }
+
+    fn backfill(
+        &self,
+        symbol: String,
+        points: u32,
+        interval_ms: u64,
+    ) -> Result<Vec<PriceUpdate>, TickerError> {
+        if points == 0 {
+            return Ok(Vec::new());
+        }
+        let config = lock(&self.config).clone();
+        let metadata = self.symbol_metadata(&symbol);
+        let model = PriceModel::resolve(&metadata, &config);
+
+        // Use the source's history as given, keeping only the newest points.
+        let source = lock(&self.price_source).clone();
+        let provided = source
+            .and_then(|source| source.fetch_history(symbol.clone(), points, interval_ms))
+            .filter(|prices| {
+                !prices.is_empty() && prices.iter().all(|price| price.is_finite() && *price > 0.0)
+            });
+        let prices: Vec<f64> = match provided {
+            Some(prices) => {
+                let skip = prices.len().saturating_sub(points as usize);
+                prices[skip..]
+                    .iter()
+                    .map(|price| model.round(price.max(model.floor)))
+                    .collect()
+            }
+            None => {
+                let latest = lock(&self.latest).get(&symbol).map(|update| update.price);
+                let anchor = match latest {
+                    Some(price) => price,
+                    None => self.quote(symbol.clone())?.price,
+                };
+                // Walk forward from the anchor and reverse, so the path ends
+                // exactly at it.
+                let mut sim = SimCore::new(config.seed.map(|seed| seed ^ symbol_hash(&symbol)));
+                sim.add_symbol(
+                    &SymbolMetadata {
+                        base_price: Some(anchor),
+                        ..metadata
+                    },
+                    &config,
+                );
+                let noise_scale = (interval_ms as f64 / TICK_INTERVAL_MS as f64).sqrt();
+                let mut path = vec![anchor];
+                for _ in 1..points {
+                    if let Some(price) = sim.walk(&symbol, &model, noise_scale) {
+                        path.push(price);
+                    }
+                }
+                path.reverse();
+                path
+            }
+        };
+
+        let now_ms = current_timestamp_ms();
+        let last = prices.len() - 1;
+        let mut prev_price = prices[0];
+        let updates: Vec<PriceUpdate> = prices
+            .iter()
+            .enumerate()
+            .map(|(index, &price)| {
+                let back_ms = interval_ms.saturating_mul((last - index) as u64);
+                let update = PriceUpdate {
+                    symbol: symbol.clone(),
+                    price,
+                    timestamp_ms: now_ms.saturating_sub(i64::try_from(back_ms).unwrap_or(i64::MAX)),
+                    kind: UpdateKind::Tick,
+                    reason: None,
+                    direction: TickDirection::between(prev_price, price),
+                    prev_price,
+                    sequence: 0,
+                };
+                prev_price = price;
+                update
+            })
+            .collect();
+
+        // A symbol in the running session already walks from its own price.
+        if !lock(&self.tracked).contains(&symbol) {
+            lock(&self.backfill_anchors).insert(symbol.clone(), prices[last]);
+        }
+        // The path ends at the current price, so it only joins up with a
+        // history that has nothing in it yet.
+        if config.backfill_into_history {
+            let mut history = lock(&self.history);
+            let retained = history.entry(symbol).or_default();
+            if retained.is_empty() {
+                let skip = updates
+                    .len()
+                    .saturating_sub(config.history_capacity as usize);
+                retained.extend(updates[skip..].iter().cloned());
+            }
+        }
+        Ok(updates)
+    }
+
+    // Returns true when this tick's price is fully scripted and gets no noise.
+    fn apply_scripted_moves(&self, symbol: &str, price: &mut f64, scenario_noise: bool) -> bool {
+        let mut silenced = false;
+        if let Some(target) = lock(&self.price_overrides).remove(symbol) {
+            *price = target;
+            silenced = true;
+        }
+
+        let mut active = lock(&self.scenario);
+        if let Some(scenario) = active.as_mut() {
+            silenced |= scenario.apply_due(symbol, price);
+            silenced |= !scenario_noise && scenario.touches(symbol);
+            if scenario.is_finished() {
+                *active = None;
+            }
+        }
+        silenced
+    }
+
+    fn skip_scripted_moves(&self, symbol: &str) {
+        let mut active = lock(&self.scenario);
+        if let Some(scenario) = active.as_mut() {
+            scenario.skip_due(symbol);
+            if scenario.is_finished() {
+                *active = None;
+            }
+        }
+    }
+
+    fn schedule_action(&self, symbol: String, kind: CorporateActionKind, at_ms: i64) {
+        lock(&self.corporate_actions).push(ScheduledAction {
+            symbol,
+            kind,
+            at_ms,
+        });
+    }
+
+    // Due actions adjust the walk's price before this tick's move, so history
+    // picks up the adjusted level without rewriting earlier entries. Actions
+    // for symbols that aren't ticking stay queued until they are.
+    fn apply_corporate_actions(
+        &self,
+        symbol: &str,
+        price: &mut f64,
+        model: &PriceModel,
+        now_ms: i64,
+    ) {
+        let mut due: Vec<ScheduledAction> = {
+            let mut scheduled = lock(&self.corporate_actions);
+            let (due, pending) = scheduled
+                .drain(..)
+                .partition(|action| action.symbol == symbol && action.at_ms <= now_ms);
+            *scheduled = pending;
+            due
+        };
+        due.sort_by_key(|action| action.at_ms);
+
+        for action in due {
+            let price_before = *price;
+            let adjusted = match action.kind {
+                CorporateActionKind::Split { ratio } => {
+                    lock(&self.portfolio).apply_split(symbol, ratio);
+                    apply_split_to_orders(&mut lock(&self.orders), symbol, ratio);
+                    price_before / ratio
+                }
+                CorporateActionKind::Dividend { amount } => {
+                    lock(&self.portfolio).apply_dividend(symbol, amount);
+                    price_before - amount
+                }
+            };
+            *price = model.round(adjusted.max(model.floor));
+            self.events.emit(EngineEvent::CorporateAction {
+                action: CorporateAction {
+                    symbol: symbol.to_string(),
+                    kind: action.kind,
+                    scheduled_ms: action.at_ms,
+                    applied_ms: now_ms,
+                    price_before,
+                    price_after: *price,
+                },
+            });
+        }
+    }
+
+    fn announce_halt_changes(&self, sim: &SimCore, config: &TickerConfig) {
+        let changes: Vec<HaltChange> = lock(&self.halt_changes).drain(..).collect();
+        for change in changes {
+            let (symbol, kind, reason, event) = match change {
+                HaltChange::Halt { symbol, reason } => (
+                    symbol.clone(),
+                    UpdateKind::Halt,
+                    Some(reason.clone()),
+                    EngineEvent::Halted { symbol, reason },
+                ),
+                HaltChange::Resume { symbol } => (
+                    symbol.clone(),
+                    UpdateKind::Resume,
+                    None,
+                    EngineEvent::Resumed { symbol },
+                ),
+            };
+            let Some(price) = sim.price(&symbol) else {
+                continue;
+            };
+            self.events.emit(event);
+            let update = PriceUpdate {
+                symbol: symbol.clone(),
+                price,
+                timestamp_ms: current_timestamp_ms(),
+                kind,
+                reason,
+                direction: TickDirection::Unchanged,
+                prev_price: price,
+                sequence: self.next_sequence(),
+            };
+            // While halted, `latest_prices` keeps serving this update so the
+            // pre-halt price reads as halted.
+            self.deliver(update, config);
+        }
+    }
+
+    fn symbol_metadata(&self, symbol: &str) -> SymbolMetadata {
+        lock(&self.metadata)
+            .get(symbol)
+            .cloned()
+            .unwrap_or_else(|| default_metadata(symbol))
+    }
+
+    // An unregistered inverse leg formats with the pip the tick loop derives
+    // for it, from its direct pair's latest price or else from `price`.
+    fn display_metadata(&self, symbol: &str, price: f64) -> SymbolMetadata {
+        let metadata = self.symbol_metadata(symbol);
+        if lock(&self.metadata).contains_key(symbol) {
+            return metadata;
+        }
+        let Some(direct) = inverse_symbol(symbol).map(|direct| self.symbol_metadata(&direct))
+        else {
+            return metadata;
+        };
+        if !matches!(
+            direct.kind,
+            SymbolKind::Fx {
+                track_inverse: true,
+                ..
+            }
+        ) {
+            return metadata;
+        }
+        let direct_price = lock(&self.latest)
+            .get(&direct.symbol)
+            .map_or(1.0 / price, |update| update.price);
+        self.inverse_metadata(symbol, &direct, direct_price)
+    }
+
+    fn inverse_metadata(
+        &self,
+        inverse: &str,
+        direct: &SymbolMetadata,
+        direct_price: f64,
+    ) -> SymbolMetadata {
+        lock(&self.metadata)
+            .get(inverse)
+            .cloned()
+            .unwrap_or_else(|| derived_inverse_metadata(inverse, direct, direct_price))
+    }
+
+    // Conflated ticks still move the book and can fill orders, but are not
+    // recorded or delivered.
+    #[allow(clippy::too_many_arguments)]
+    fn publish(
+        &self,
+        symbol: &str,
+        price: f64,
+        model: &PriceModel,
+        timestamp_ms: i64,
+        config: &TickerConfig,
+        conflated: bool,
+        rng: &mut StdRng,
+    ) {
+        let book = build_book(
+            symbol,
+            price,
+            model.tick,
+            model.spread,
+            config.book_level_ticks,
+            timestamp_ms,
+            rng,
+        );
+        lock(&self.books).insert(symbol.to_string(), book.clone());
+        let fills = match_orders(&mut lock(&self.orders), price, &book, timestamp_ms);
+
+        if !conflated {
+            // `latest` only ever holds delivered updates, and only the tick
+            // loop writes it while running. The first tick of a session has
+            // no previous price and reads as unchanged.
+            let prev_price = lock(&self.latest)
+                .get(symbol)
+                .map_or(price, |previous| previous.price);
+            let update = PriceUpdate {
+                symbol: symbol.to_string(),
+                price,
+                timestamp_ms,
+                kind: UpdateKind::Tick,
+                reason: None,
+                direction: TickDirection::between(prev_price, price),
+                prev_price,
+                sequence: self.next_sequence(),
+            };
+            self.record_history(&update, config.history_capacity);
+            self.deliver(update, config);
+        }
+
+        if !fills.is_empty() {
+            let mut portfolio = lock(&self.portfolio);
+            for fill in &fills {
+                portfolio.apply_fill(fill);
+            }
+        }
+        for fill in fills {
+            self.events.emit(EngineEvent::Fill { fill });
+        }
+
+        if config.emit_books {
+            self.events.emit(EngineEvent::Book {
+                symbol: symbol.to_string(),
+                book,
+            });
+        }
+    }
+
+    // Only the tick loop stamps and delivers, each sequence right before its
+    // update is fanned out, so sequence order is delivery order at every
+    // listener. Conflated ticks are never stamped, leaving no holes.
+    fn next_sequence(&self) -> u64 {
+        self.next_sequence.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn record_history(&self, update: &PriceUpdate, capacity: u32) {
+        let mut history = lock(&self.history);
+        let updates = history.entry(update.symbol.clone()).or_default();
+        updates.push_back(update.clone());
+        while updates.len() > capacity as usize {
+            updates.pop_front();
+        }
+    }
+
+    // Caches, fans out, and enqueues one update. Fan-out happens under the
+    // listener lock so a concurrent `add_listener` snapshot is always queued
+    // ahead of the first live update the new listener sees.
+    fn deliver(&self, update: PriceUpdate, config: &TickerConfig) {
+        {
+            let listeners = lock(&self.listeners);
+            lock(&self.latest).insert(update.symbol.clone(), update.clone());
+            for entry in listeners.iter().filter(|entry| entry.wants(&update.symbol)) {
+                entry.deliver(update.clone());
+            }
+        }
+
+        self.record(&update);
+        self.enqueue(update, config);
+    }
+
+    fn enqueue(&self, update: PriceUpdate, config: &TickerConfig) {
+        let priority = lock(&self.priority);
+        let mut updates = lock(&self.updates);
+        let dropped = updates.push(
+            update,
+            config.queue_capacity,
+            config.queue_policy,
+            config.retention_capacity,
+            &priority,
+        );
+        if dropped > 0 {
+            self.events.emit(EngineEvent::Backpressure {
+                dropped,
+                pending: updates.pending(),
+            });
+        }
+    }
+
+    fn record(&self, update: &PriceUpdate) {
+        let mut recorder = lock(&self.recorder);
+        let Some(active) = recorder.as_mut() else {
+            return;
         };
+        if let Err(err) = active.append(update) {
+            *recorder = None;
+            self.events.emit(EngineEvent::Error {
+                message: format!("recording stopped: {err}"),
+            });
+        }
+    }
 
-        for _ in 0..max {
-            if let Some(update) = queue.pop_front() {
-                updates.push(update);
-            } else {
-                break;
+    fn drop_stalled_listeners(&self, timeout: Duration) {
+        let mut stalled = Vec::new();
+        lock(&self.listeners).retain(|entry| match entry.busy_for() {
+            Some(busy) if busy >= timeout => {
+                entry.mark_stalled();
+                stalled.push((entry.id, busy.as_millis() as u64));
+                false
             }
+            _ => true,
+        });
+        for (id, duration_ms) in stalled {
+            self.events
+                .emit(EngineEvent::ListenerStalled { id, duration_ms });
         }
+    }
+}
+
+// Runs beside the tick loop: a stuck callback can't be aborted, but it can be
+// detected and cut off from further updates.
+async fn watch_listeners(state: Arc<EngineState>) {
+    loop {
+        let timeout_ms = lock(&state.config).listener_timeout_ms;
+        let poll_ms = timeout_ms.map_or(500, |timeout| (timeout / 4).clamp(10, 500));
+        sleep(Duration::from_millis(poll_ms)).await;
+        if let Some(timeout) = timeout_ms {
+            state.drop_stalled_listeners(Duration::from_millis(timeout));
+        }
+    }
+}
+
+// Every field is checked; those not listed here (seed, limits, rate limit,
+// policies, flags) accept any value.
+fn validate_config(config: &TickerConfig) -> Result<(), TickerError> {
+    let invalid = |field: &str, reason: &str| TickerError::InvalidConfig {
+        field: field.to_string(),
+        reason: reason.to_string(),
+    };
+    if !config.spread.is_finite() || config.spread < 0.0 {
+        return Err(invalid("spread", "must be finite and non-negative"));
+    }
+    if config.book_level_ticks == 0 {
+        return Err(invalid("book_level_ticks", "must be at least 1"));
+    }
+    // Stats windows and `get_updates_between` read the retained history.
+    if config.history_capacity == 0 {
+        return Err(invalid("history_capacity", "must be at least 1"));
+    }
+    if config.queue_capacity == Some(0) {
+        return Err(invalid(
+            "queue_capacity",
+            "must be at least 1, or None for no cap",
+        ));
+    }
+    if config.retention_capacity == 0 {
+        return Err(invalid("retention_capacity", "must be at least 1"));
+    }
+    // A zero timeout would drop every listener that is mid-callback.
+    if config.listener_timeout_ms == Some(0) {
+        return Err(invalid(
+            "listener_timeout_ms",
+            "must be positive, or None to disable",
+        ));
+    }
+    Ok(())
+}
+
+// Keeps the book generator's stream apart from the walk's under one seed.
+const BOOK_SEED_SALT: u64 = 0x626f_6f6b;
+
+// FNV-1a; stable across runs and platforms, unlike the std hasher.
+fn symbol_hash(symbol: &str) -> u64 {
+    symbol.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
 
-        updates
+// Shared by every engine's tick loop and by one-shot quote fetches.
+fn shared_runtime() -> Result<&'static Runtime, String> {
+    static RUNTIME: OnceLock<Result<Runtime, String>> = OnceLock::new();
+    RUNTIME
+        .get_or_init(|| Runtime::new().map_err(|err| err.to_string()))
+        .as_ref()
+        .map_err(Clone::clone)
+}
+
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
     }
 }
 
-fn current_timestamp_ms() -> i64 {
+pub(crate) fn current_timestamp_ms() -> i64 {
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default();
     now.as_millis() as i64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::QueuePolicy;
+    use crate::testing::{Collect, EventLog};
+    use std::sync::atomic::AtomicUsize;
+
+    struct CountingListener {
+        calls: AtomicUsize,
+        first_call_sleep: Duration,
+    }
+
+    impl PriceListener for CountingListener {
+        fn on_price(&self, _update: PriceUpdate) {
+            if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                std::thread::sleep(self.first_call_sleep);
+            }
+        }
+    }
+
+    fn listener(first_call_sleep: Duration) -> Arc<CountingListener> {
+        Arc::new(CountingListener {
+            calls: AtomicUsize::new(0),
+            first_call_sleep,
+        })
+    }
+
+    fn seeded(seed: u64) -> TickerConfig {
+        TickerConfig {
+            seed: Some(seed),
+            ..TickerConfig::default()
+        }
+    }
+
+    fn backfilled(engine: &TickerEngine, symbol: &str, points: u32) -> Vec<PriceUpdate> {
+        Runtime::new()
+            .unwrap()
+            .block_on(engine.backfill(symbol.to_string(), points, TICK_INTERVAL_MS))
+            .unwrap()
+    }
+
+    fn rejected_field(config: TickerConfig) -> Option<String> {
+        match TickerEngine::new().apply_config(config) {
+            Err(TickerError::InvalidConfig { field, .. }) => Some(field),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn invalid_configs_name_the_offending_field() {
+        let cases = [
+            (
+                TickerConfig {
+                    spread: f64::NAN,
+                    ..TickerConfig::default()
+                },
+                "spread",
+            ),
+            (
+                TickerConfig {
+                    book_level_ticks: 0,
+                    ..TickerConfig::default()
+                },
+                "book_level_ticks",
+            ),
+            (
+                TickerConfig {
+                    history_capacity: 0,
+                    ..TickerConfig::default()
+                },
+                "history_capacity",
+            ),
+            (
+                TickerConfig {
+                    queue_capacity: Some(0),
+                    ..TickerConfig::default()
+                },
+                "queue_capacity",
+            ),
+            (
+                TickerConfig {
+                    retention_capacity: 0,
+                    ..TickerConfig::default()
+                },
+                "retention_capacity",
+            ),
+            (
+                TickerConfig {
+                    listener_timeout_ms: Some(0),
+                    ..TickerConfig::default()
+                },
+                "listener_timeout_ms",
+            ),
+        ];
+        for (config, field) in cases {
+            assert_eq!(rejected_field(config.clone()).as_deref(), Some(field));
+            assert!(TickerEngine::with_config(config).is_err());
+        }
+    }
+
+    #[test]
+    fn apply_config_swaps_the_whole_record() {
+        let engine = TickerEngine::new();
+        let config = TickerConfig {
+            seed: Some(4),
+            min_update_interval_ms: Some(250),
+            queue_capacity: Some(10),
+            ..TickerConfig::default()
+        };
+        engine.apply_config(config).unwrap();
+        let current = engine.current_config();
+        assert_eq!(current.seed, Some(4));
+        assert_eq!(current.min_update_interval_ms, Some(250));
+        assert_eq!(current.queue_capacity, Some(10));
+    }
+
+    #[test]
+    fn seed_cannot_change_while_tracking() {
+        let engine = TickerEngine::new();
+        engine.start_tracking(vec!["AAPL".to_string()], listener(Duration::ZERO));
+        let result = engine.apply_config(TickerConfig {
+            seed: Some(99),
+            ..engine.current_config()
+        });
+        engine.cancel();
+        assert_eq!(
+            result,
+            Err(TickerError::ConfigFieldImmutable {
+                field: "seed".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn drain_cursor_is_not_a_public_cursor() {
+        let engine = TickerEngine::new();
+        let unknown = TickerError::UnknownCursor { id: DRAIN_CURSOR };
+        assert_eq!(
+            engine.read_cursor(DRAIN_CURSOR, 10).err(),
+            Some(unknown.clone())
+        );
+        assert_eq!(engine.reset_cursor(DRAIN_CURSOR, 1), Err(unknown.clone()));
+        assert_eq!(engine.drop_cursor(DRAIN_CURSOR), Err(unknown));
+    }
+
+    #[test]
+    fn stalled_listener_is_dropped_and_reported() {
+        let engine = TickerEngine::with_config(TickerConfig {
+            seed: Some(1),
+            listener_timeout_ms: Some(200),
+            ..TickerConfig::default()
+        })
+        .unwrap();
+        let events = Arc::new(EventLog::default());
+        engine.add_event_listener(events.clone());
+
+        let stuck = listener(Duration::from_millis(1_500));
+        let healthy = listener(Duration::ZERO);
+        engine.start_tracking(vec!["AAPL".to_string()], stuck.clone());
+        engine.add_listener(healthy.clone(), None);
+        std::thread::sleep(Duration::from_millis(2_500));
+        engine.cancel();
+
+        let stalled = lock(&events.0)
+            .iter()
+            .filter(|event| matches!(event, EngineEvent::ListenerStalled { .. }))
+            .count();
+        assert_eq!(stalled, 1);
+        // The stuck callback returns after its sleep but gets nothing more.
+        assert_eq!(stuck.calls.load(Ordering::SeqCst), 1);
+        assert!(healthy.calls.load(Ordering::SeqCst) >= 4);
+    }
+
+    #[test]
+    fn backfill_is_seeded_and_ends_at_the_quote() {
+        let first = backfilled(&TickerEngine::with_config(seeded(7)).unwrap(), "AAPL", 50);
+        let engine = TickerEngine::with_config(seeded(7)).unwrap();
+        let second = backfilled(&engine, "AAPL", 50);
+        let prices = |updates: &[PriceUpdate]| -> Vec<f64> {
+            updates.iter().map(|update| update.price).collect()
+        };
+        assert_eq!(first.len(), 50);
+        assert_eq!(prices(&first), prices(&second));
+
+        let quote = Runtime::new()
+            .unwrap()
+            .block_on(engine.fetch_quote("AAPL".to_string()))
+            .unwrap();
+        assert_eq!(second.last().unwrap().price, quote.price);
+        assert!(second
+            .windows(2)
+            .all(|pair| pair[1].timestamp_ms - pair[0].timestamp_ms == TICK_INTERVAL_MS as i64));
+        // Nothing reaches pull consumers or the history unless asked to.
+        assert!(engine.drain_updates(u32::MAX).is_empty());
+        assert_eq!(engine.oldest_retained_ms("AAPL".to_string()), None);
+    }
+
+    #[test]
+    fn backfill_can_seed_the_history() {
+        let engine = TickerEngine::with_config(TickerConfig {
+            backfill_into_history: true,
+            ..seeded(7)
+        })
+        .unwrap();
+        let updates = backfilled(&engine, "AAPL", 20);
+        assert_eq!(
+            engine.oldest_retained_ms("AAPL".to_string()),
+            Some(updates[0].timestamp_ms)
+        );
+        assert!(engine.drain_updates(u32::MAX).is_empty());
+    }
+
+    #[test]
+    fn live_ticks_walk_on_from_the_last_backfilled_price() {
+        let config = TickerConfig {
+            max_ticks: Some(1),
+            ..seeded(7)
+        };
+        let engine = TickerEngine::with_config(config.clone()).unwrap();
+        let anchor = backfilled(&engine, "AAPL", 50).last().unwrap().price;
+        let collected = Arc::new(Collect::default());
+        engine.start_tracking(vec!["AAPL".to_string()], collected.clone());
+        std::thread::sleep(Duration::from_millis(1_500));
+
+        let simulator = crate::simulator::PriceSimulator::new(config, 7);
+        simulator.add_symbol(SymbolMetadata {
+            base_price: Some(anchor),
+            ..default_metadata("AAPL")
+        });
+        let expected = simulator.step(TICK_INTERVAL_MS)[0].price;
+        let live = lock(&collected.0)[0].price;
+        assert_eq!(live, expected);
+    }
+
+    #[test]
+    fn derived_inverse_legs_format_with_their_own_pip() {
+        let engine = TickerEngine::new();
+        assert_eq!(engine.format_price("JPYUSD".to_string(), 0.0066666), "0.01");
+        engine.register_symbol(SymbolMetadata {
+            symbol: "USDJPY".to_string(),
+            kind: SymbolKind::Fx {
+                pip_size: 0.01,
+                track_inverse: true,
+            },
+            tick_size: 0.01,
+            base_price: Some(150.0),
+            volatility: None,
+            spread: None,
+            continuous: false,
+        });
+        assert_eq!(
+            engine.format_price("USDJPY".to_string(), 150.1234),
+            "150.123"
+        );
+        assert_eq!(
+            engine.format_price("JPYUSD".to_string(), 0.006666612),
+            "0.0066666"
+        );
+    }
+
+    #[test]
+    fn shed_updates_are_reported_as_backpressure() {
+        let engine = TickerEngine::with_config(TickerConfig {
+            max_ticks: Some(2),
+            queue_capacity: Some(1),
+            queue_policy: QueuePolicy::DropNewest,
+            ..seeded(3)
+        })
+        .unwrap();
+        let events = Arc::new(EventLog::default());
+        engine.add_event_listener(events.clone());
+        let symbols = ["AAPL", "MSFT", "GOOG"].map(String::from).to_vec();
+        engine.start_tracking(symbols, listener(Duration::ZERO));
+        std::thread::sleep(Duration::from_millis(1_500));
+
+        let dropped: u32 = lock(&events.0)
+            .iter()
+            .map(|event| match event {
+                EngineEvent::Backpressure {
+                    dropped,
+                    pending: 1,
+                } => *dropped,
+                _ => 0,
+            })
+            .sum();
+        // Two ticks of three symbols into a queue of one.
+        assert_eq!(dropped, 5);
+        assert_eq!(engine.drain_updates(u32::MAX).len(), 1);
+    }
+
+    #[test]
+    fn steps_for_a_halted_symbol_lapse() {
+        let engine = TickerEngine::with_config(seeded(5)).unwrap();
+        let symbols = ["AAPL", "MSFT"].map(String::from).to_vec();
+        engine.start_tracking(symbols, listener(Duration::ZERO));
+        engine
+            .halt_symbol("AAPL".to_string(), "news".to_string())
+            .unwrap();
+        let step = |symbol: &str| ScenarioStep {
+            at_ms: 0,
+            symbol: symbol.to_string(),
+            price: Some(50.0),
+            delta_pct: None,
+        };
+        engine.run_scenario(vec![step("AAPL")]).unwrap();
+        std::thread::sleep(Duration::from_millis(1_200));
+
+        let rerun = engine.run_scenario(vec![step("MSFT")]);
+        engine.resume_symbol("AAPL".to_string()).unwrap();
+        std::thread::sleep(Duration::from_millis(700));
+        let aapl = engine
+            .latest_prices()
+            .into_iter()
+            .find(|update| update.symbol == "AAPL")
+            .map(|update| update.price);
+        engine.cancel();
+        assert_eq!(rerun, Ok(()));
+        // The lapsed step doesn't fire on resume.
+        assert!(aapl.is_some_and(|price| price != 50.0), "{aapl:?}");
+    }
+
+    #[test]
+    fn backfill_leaves_a_live_session_and_its_history_alone() {
+        let engine = TickerEngine::with_config(TickerConfig {
+            backfill_into_history: true,
+            ..seeded(7)
+        })
+        .unwrap();
+        engine.start_tracking(vec!["AAPL".to_string()], listener(Duration::ZERO));
+        std::thread::sleep(Duration::from_millis(700));
+        let live = || {
+            engine
+                .get_updates_between("AAPL".to_string(), i64::MIN, i64::MAX)
+                .iter()
+                .map(|update| (update.timestamp_ms, update.price))
+                .collect::<Vec<_>>()
+        };
+        let before = live();
+        backfilled(&engine, "AAPL", 50);
+        let after = live();
+        engine.cancel();
+
+        assert!(!before.is_empty());
+        assert_eq!(after[..before.len()], before[..]);
+        assert!(lock(&engine.state.backfill_anchors).is_empty());
+    }
+}
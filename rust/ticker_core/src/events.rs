@@ -0,0 +1,76 @@
+use crate::api::{BookListener, EngineEvent, EventListener, FillListener};
+use crate::engine::lock;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc, Arc, Mutex,
+};
+
+type Listeners = Arc<Mutex<Vec<(u64, Arc<dyn EventListener>)>>>;
+
+// Single ordered channel for every engine event. The dispatch thread only
+// holds the listener list, so it exits once the bus (and its sender) drops.
+pub(crate) struct EventBus {
+    sender: Mutex<mpsc::Sender<EngineEvent>>,
+    listeners: Listeners,
+    next_id: AtomicU64,
+}
+
+impl EventBus {
+    pub(crate) fn new() -> Self {
+        let (sender, receiver) = mpsc::channel::<EngineEvent>();
+        let listeners: Listeners = Arc::new(Mutex::new(Vec::new()));
+
+        let dispatch_listeners = listeners.clone();
+        std::thread::spawn(move || {
+            while let Ok(event) = receiver.recv() {
+                let listeners: Vec<Arc<dyn EventListener>> = lock(&dispatch_listeners)
+                    .iter()
+                    .map(|(_, listener)| listener.clone())
+                    .collect();
+                for listener in listeners {
+                    listener.on_event(event.clone());
+                }
+            }
+        });
+
+        Self {
+            sender: Mutex::new(sender),
+            listeners,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub(crate) fn emit(&self, event: EngineEvent) {
+        let _ = lock(&self.sender).send(event);
+    }
+
+    pub(crate) fn add_listener(&self, listener: Arc<dyn EventListener>) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        lock(&self.listeners).push((id, listener));
+        id
+    }
+
+    pub(crate) fn remove_listener(&self, id: u64) {
+        lock(&self.listeners).retain(|(listener_id, _)| *listener_id != id);
+    }
+}
+
+pub(crate) struct BookAdapter(pub(crate) Arc<dyn BookListener>);
+
+impl EventListener for BookAdapter {
+    fn on_event(&self, event: EngineEvent) {
+        if let EngineEvent::Book { symbol, book } = event {
+            self.0.on_book(symbol, book);
+        }
+    }
+}
+
+pub(crate) struct FillAdapter(pub(crate) Arc<dyn FillListener>);
+
+impl EventListener for FillAdapter {
+    fn on_event(&self, event: EngineEvent) {
+        if let EngineEvent::Fill { fill } = event {
+            self.0.on_fill(fill);
+        }
+    }
+}
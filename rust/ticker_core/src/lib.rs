@@ -1,7 +1,28 @@
 mod api;
+mod book;
+mod codec;
+mod cursors;
 mod engine;
+mod events;
+mod listeners;
+mod pricing;
+mod recording;
+mod scenario;
+mod simulator;
+mod stats;
+// Listener fixtures shared by the unit tests of several modules.
+#[cfg(test)]
+mod testing;
+mod trading;
 
-pub use api::{PriceListener, PriceUpdate};
+pub use api::{
+    inverted_price, BookLevel, BookListener, CorporateAction, CorporateActionKind, EngineEvent,
+    EventListener, Fill, FillListener, OrderBook, OrderSide, Position, PriceListener, PriceSource,
+    PriceUpdate, QueuePolicy, ScenarioStep, StopReason, SymbolKind, SymbolMetadata, TickDirection,
+    TickerConfig, TickerError, UpdateKind, WindowStats,
+};
+pub use codec::{decode_updates, EncodeFormat};
 pub use engine::TickerEngine;
+pub use simulator::PriceSimulator;
 
 uniffi::setup_scaffolding!();
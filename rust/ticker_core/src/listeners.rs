@@ -0,0 +1,234 @@
+#[cfg(feature = "ordering-checks")]
+use crate::api::UpdateKind;
+use crate::api::{PriceListener, PriceUpdate};
+use crate::engine::lock;
+#[cfg(feature = "ordering-checks")]
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc, Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+
+// Shared between the registry entry and its dispatch thread so the watchdog
+// can see how long the current callback has been running.
+struct DispatchState {
+    stalled: AtomicBool,
+    call_started: Mutex<Option<Instant>>,
+}
+
+// Each listener is fed by its own thread, so a callback that never returns
+// only wedges that listener, not the tick loop or other subscribers. The
+// channel is the listener's only path, so it sees updates in send order.
+pub(crate) struct ListenerEntry {
+    pub(crate) id: u64,
+    symbols: Option<HashSet<String>>,
+    sender: mpsc::Sender<PriceUpdate>,
+    dispatch: Arc<DispatchState>,
+}
+
+impl ListenerEntry {
+    pub(crate) fn new(
+        id: u64,
+        listener: Arc<dyn PriceListener>,
+        symbols: Option<Vec<String>>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel::<PriceUpdate>();
+        let dispatch = Arc::new(DispatchState {
+            stalled: AtomicBool::new(false),
+            call_started: Mutex::new(None),
+        });
+
+        let thread_dispatch = dispatch.clone();
+        std::thread::spawn(move || {
+            #[cfg(feature = "ordering-checks")]
+            let mut last_sequence: HashMap<String, u64> = HashMap::new();
+            // Ends once the entry is dropped and the backlog is delivered, or
+            // as soon as the watchdog has marked the listener stalled.
+            while let Ok(update) = receiver.recv() {
+                if thread_dispatch.stalled.load(Ordering::SeqCst) {
+                    break;
+                }
+                #[cfg(feature = "ordering-checks")]
+                check_order(&mut last_sequence, id, &update);
+                *lock(&thread_dispatch.call_started) = Some(Instant::now());
+                listener.on_price(update);
+                *lock(&thread_dispatch.call_started) = None;
+            }
+        });
+
+        Self {
+            id,
+            symbols: symbols.map(|symbols| symbols.into_iter().collect()),
+            sender,
+            dispatch,
+        }
+    }
+
+    pub(crate) fn wants(&self, symbol: &str) -> bool {
+        self.symbols
+            .as_ref()
+            .is_none_or(|symbols| symbols.contains(symbol))
+    }
+
+    pub(crate) fn deliver(&self, update: PriceUpdate) {
+        if !self.dispatch.stalled.load(Ordering::SeqCst) {
+            let _ = self.sender.send(update);
+        }
+    }
+
+    // How long the in-flight callback has run, if one is running.
+    pub(crate) fn busy_for(&self) -> Option<Duration> {
+        lock(&self.dispatch.call_started).map(|started| started.elapsed())
+    }
+
+    pub(crate) fn mark_stalled(&self) {
+        self.dispatch.stalled.store(true, Ordering::SeqCst);
+    }
+}
+
+// Snapshots repeat their source's sequence, so they may equal the last one
+// seen but never go backwards. Unsequenced updates are ignored.
+#[cfg(feature = "ordering-checks")]
+fn check_order(last_sequence: &mut HashMap<String, u64>, id: u64, update: &PriceUpdate) {
+    if update.sequence == 0 {
+        return;
+    }
+    let previous = last_sequence.insert(update.symbol.clone(), update.sequence);
+    if let Some(previous) = previous {
+        let in_order = match update.kind {
+            UpdateKind::Snapshot => update.sequence >= previous,
+            _ => update.sequence > previous,
+        };
+        assert!(
+            in_order,
+            "listener {id} got {} sequence {} after {previous}",
+            update.symbol, update.sequence
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{PriceListener, PriceUpdate, TickerConfig, UpdateKind};
+    use crate::engine::{lock, TickerEngine};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    // Sleeps on every `every`th callback so dispatch threads fall behind the
+    // tick loop by different amounts.
+    struct SlowListener {
+        every: usize,
+        sleep: Duration,
+        received: Mutex<Vec<PriceUpdate>>,
+    }
+
+    impl PriceListener for SlowListener {
+        fn on_price(&self, update: PriceUpdate) {
+            let count = {
+                let mut received = lock(&self.received);
+                received.push(update);
+                received.len()
+            };
+            if count % self.every == 0 {
+                std::thread::sleep(self.sleep);
+            }
+        }
+    }
+
+    fn slow(every: usize, sleep_ms: u64) -> Arc<SlowListener> {
+        Arc::new(SlowListener {
+            every,
+            sleep: Duration::from_millis(sleep_ms),
+            received: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn assert_per_symbol_order(name: &str, received: &[PriceUpdate]) {
+        let mut last: HashMap<&str, u64> = HashMap::new();
+        for update in received {
+            assert_ne!(update.sequence, 0, "{name}: unsequenced delivery");
+            if let Some(previous) = last.insert(&update.symbol, update.sequence) {
+                match update.kind {
+                    UpdateKind::Snapshot => assert!(update.sequence >= previous),
+                    _ => assert!(
+                        update.sequence > previous,
+                        "{name}: {} went {previous} -> {}",
+                        update.symbol,
+                        update.sequence
+                    ),
+                }
+            }
+        }
+    }
+
+    fn sequences(received: &[PriceUpdate]) -> Vec<u64> {
+        received
+            .iter()
+            .filter(|update| update.kind != UpdateKind::Snapshot)
+            .map(|update| update.sequence)
+            .collect()
+    }
+
+    #[test]
+    fn slow_listeners_see_each_symbol_in_sequence_order() {
+        let symbols: Vec<String> = (0..400).map(|index| format!("S{index:03}")).collect();
+        let engine = TickerEngine::with_config(TickerConfig {
+            seed: Some(11),
+            max_ticks: Some(6),
+            ..TickerConfig::default()
+        })
+        .unwrap();
+
+        let session = slow(1_000_000, 0);
+        let early = [slow(7, 1), slow(50, 20), slow(3, 0)];
+        for listener in &early {
+            engine.add_listener(listener.clone(), None);
+        }
+        let filtered = slow(5, 2);
+        engine.add_listener(filtered.clone(), Some(symbols[..40].to_vec()));
+        engine.start_tracking(symbols.clone(), session.clone());
+
+        // Halts and a late subscriber mix non-tick updates and snapshots in.
+        std::thread::sleep(Duration::from_millis(700));
+        engine
+            .halt_symbol(symbols[1].clone(), "test".to_string())
+            .unwrap();
+        let late = slow(11, 3);
+        engine.add_listener(late.clone(), None);
+        std::thread::sleep(Duration::from_millis(600));
+        engine.resume_symbol(symbols[1].clone()).unwrap();
+
+        // Six ticks take about three seconds; the loop then stops itself.
+        std::thread::sleep(Duration::from_millis(2_200));
+        let delivered = engine.drain_updates(u32::MAX);
+        let all = sequences(&delivered);
+        assert!(all.len() >= 2_000, "only {} updates", all.len());
+
+        // Every full subscriber gets every update exactly once.
+        let full = early.iter().chain(std::iter::once(&session));
+        for (index, listener) in full.enumerate() {
+            let deadline = Instant::now() + Duration::from_secs(20);
+            while lock(&listener.received).len() < all.len() && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            let received = lock(&listener.received);
+            assert_per_symbol_order(&format!("listener {index}"), &received);
+            assert_eq!(sequences(&received), all, "listener {index}");
+        }
+
+        std::thread::sleep(Duration::from_millis(500));
+        let filtered = lock(&filtered.received);
+        assert_per_symbol_order("filtered", &filtered);
+        assert!(filtered
+            .iter()
+            .all(|update| symbols[..40].contains(&update.symbol)));
+        let late = lock(&late.received);
+        assert_per_symbol_order("late", &late);
+        assert!(late
+            .iter()
+            .any(|update| update.kind == UpdateKind::Snapshot));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::api::{SymbolKind, SymbolMetadata, TickerConfig};
+
+pub(crate) const DEFAULT_TICK_SIZE: f64 = 0.01;
+
+const MIN_TICK_SIZE: f64 = 1e-8;
+const MAX_DECIMALS: u32 = 8;
+const EQUITY_VOLATILITY: f64 = 1.0;
+// The equity defaults are tuned for prices around this level.
+const EQUITY_REFERENCE_PRICE: f64 = 100.0;
+const FX_VOLATILITY_PIPS: f64 = 2.0;
+const FX_SPREAD_PIPS: f64 = 1.5;
+
+// Everything the walk, book, and formatting need for one symbol, resolved to
+// price units so callers never have to care whether the symbol is FX-typed.
+pub(crate) struct PriceModel {
+    pub(crate) tick: f64,
+    pub(crate) volatility: f64,
+    pub(crate) spread: f64,
+    pub(crate) floor: f64,
+}
+
+impl PriceModel {
+    pub(crate) fn resolve(metadata: &SymbolMetadata, config: &TickerConfig) -> Self {
+        match metadata.kind {
+            SymbolKind::Equity => {
+                let tick = sanitize_tick(metadata.tick_size);
+                let scale = match metadata.base_price {
+                    Some(base) if metadata.continuous && base.is_finite() && base > 0.0 => {
+                        base / EQUITY_REFERENCE_PRICE
+                    }
+                    _ => 1.0,
+                };
+                Self {
+                    tick,
+                    volatility: metadata
+                        .volatility
+                        .unwrap_or(EQUITY_VOLATILITY * scale)
+                        .abs(),
+                    spread: metadata.spread.unwrap_or(config.spread * scale),
+                    floor: tick,
+                }
+            }
+            SymbolKind::Fx { pip_size, .. } => {
+                let pip = sanitize_tick(pip_size);
+                Self {
+                    tick: sanitize_tick(pip / 10.0),
+                    volatility: metadata.volatility.unwrap_or(FX_VOLATILITY_PIPS).abs() * pip,
+                    spread: metadata.spread.unwrap_or(FX_SPREAD_PIPS) * pip,
+                    floor: pip,
+                }
+            }
+        }
+    }
+
+    pub(crate) fn default_base_range(metadata: &SymbolMetadata) -> (f64, f64) {
+        match metadata.kind {
+            SymbolKind::Equity => (90.0, 110.0),
+            // 10_000 pips puts EURUSD-style pairs near 1.0 and JPY pairs near 100.
+            SymbolKind::Fx { pip_size, .. } => {
+                let pip = sanitize_tick(pip_size);
+                (9_000.0 * pip, 11_000.0 * pip)
+            }
+        }
+    }
+
+    pub(crate) fn round(&self, price: f64) -> f64 {
+        ticks_to_price((price / self.tick).round() as i64, self.tick)
+    }
+
+    pub(crate) fn format(&self, price: f64) -> String {
+        format!("{:.*}", tick_decimals(self.tick) as usize, price)
+    }
+}
+
+// "EURUSD" -> "USDEUR", "EUR/USD" -> "USD/EUR"; anything else gets no inverse.
+pub(crate) fn inverse_symbol(symbol: &str) -> Option<String> {
+    if let Some((base, quote)) = symbol.split_once('/') {
+        return Some(format!("{quote}/{base}"));
+    }
+    if symbol.len() == 6 && symbol.is_ascii() {
+        let (base, quote) = symbol.split_at(3);
+        return Some(format!("{quote}{base}"));
+    }
+    None
+}
+
+// Number of decimals needed to represent multiples of `tick` exactly.
+pub(crate) fn tick_decimals(tick: f64) -> u32 {
+    (0..=MAX_DECIMALS)
+        .find(|&decimals| {
+            let scaled = tick * 10f64.powi(decimals as i32);
+            (scaled - scaled.round()).abs() < 1e-9
+        })
+        .unwrap_or(MAX_DECIMALS)
+}
+
+// Snaps an integer number of ticks back to a price without leaking f64
+// artifacts such as 100.12000000000001.
+pub(crate) fn ticks_to_price(ticks: i64, tick: f64) -> f64 {
+    let scale = 10f64.powi(tick_decimals(tick) as i32);
+    (ticks as f64 * tick * scale).round() / scale
+}
+
+// Ticks are snapped to the 8-decimal grid so `tick_decimals` can always
+// represent them; 2.5e-8 would otherwise round prices off-tick.
+pub(crate) fn sanitize_tick(tick: f64) -> f64 {
+    if tick.is_finite() && tick > 0.0 {
+        let scale = 10f64.powi(MAX_DECIMALS as i32);
+        ((tick * scale).round() / scale).max(MIN_TICK_SIZE)
+    } else {
+        DEFAULT_TICK_SIZE
+    }
+}
+
+// What an unregistered symbol ticks as.
+pub(crate) fn default_metadata(symbol: &str) -> SymbolMetadata {
+    SymbolMetadata {
+        symbol: symbol.to_string(),
+        kind: SymbolKind::Equity,
+        tick_size: DEFAULT_TICK_SIZE,
+        base_price: None,
+        volatility: None,
+        spread: None,
+        continuous: false,
+    }
+}
+
+// An unregistered inverse leg scales the direct pair's pip to the inverse
+// price's magnitude (d(1/p) = dp / p²), snapped to a power of ten, so a
+// USDJPY pip of 0.01 at 150 gives JPYUSD a pip of 0.000001.
+pub(crate) fn derived_inverse_metadata(
+    inverse: &str,
+    direct: &SymbolMetadata,
+    direct_price: f64,
+) -> SymbolMetadata {
+    let direct_pip = match direct.kind {
+        SymbolKind::Fx { pip_size, .. } => sanitize_tick(pip_size),
+        SymbolKind::Equity => DEFAULT_TICK_SIZE,
+    };
+    let pip_size = if direct_price.is_finite() && direct_price > 0.0 {
+        let scaled = direct_pip / (direct_price * direct_price);
+        // Fractional pips must stay representable as ticks.
+        10f64
+            .powi(scaled.log10().round() as i32)
+            .max(MIN_TICK_SIZE * 10.0)
+    } else {
+        direct_pip
+    };
+    SymbolMetadata {
+        symbol: inverse.to_string(),
+        kind: SymbolKind::Fx {
+            pip_size,
+            track_inverse: false,
+        },
+        tick_size: DEFAULT_TICK_SIZE,
+        base_price: None,
+        volatility: direct.volatility,
+        spread: direct.spread,
+        continuous: direct.continuous,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_snap_to_eight_decimals() {
+        assert_eq!(sanitize_tick(2.5e-8), 3e-8);
+        assert_eq!(sanitize_tick(1e-12), MIN_TICK_SIZE);
+        assert_eq!(sanitize_tick(0.01), 0.01);
+        assert_eq!(sanitize_tick(-1.0), DEFAULT_TICK_SIZE);
+        assert_eq!(sanitize_tick(f64::NAN), DEFAULT_TICK_SIZE);
+    }
+
+    #[test]
+    fn fine_ticks_round_without_artifacts() {
+        let metadata = SymbolMetadata {
+            tick_size: 2.5e-8,
+            ..default_metadata("MICRO")
+        };
+        let model = PriceModel::resolve(&metadata, &TickerConfig::default());
+        let price = model.round(0.000012345);
+        let ticks = price / model.tick;
+        assert!((ticks - ticks.round()).abs() < 1e-6, "{price} is off-tick");
+        assert_eq!(model.format(price), format!("{price:.8}"));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::api::{PriceUpdate, TickerError};
+use crate::codec::{decode_updates, encode_updates, EncodeFormat};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+
+// Recording files are a sequence of `len: u32 LE | binary batch of one
+// update` frames. Appending whole frames keeps the file readable up to the
+// last complete record if the process dies mid-write, and reopening cuts a
+// torn tail off before appending so new frames stay readable.
+pub(crate) struct Recorder {
+    writer: BufWriter<File>,
+    flush_every: u32,
+    unflushed: u32,
+}
+
+impl Recorder {
+    pub(crate) fn open(path: &str, flush_every: u32) -> Result<Self, TickerError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(io_error)?;
+        let data = std::fs::read(path).map_err(io_error)?;
+        let complete = complete_frames(&data).len();
+        if complete < data.len() {
+            file.set_len(complete as u64).map_err(io_error)?;
+        }
+        Ok(Self {
+            writer: BufWriter::new(file),
+            flush_every: flush_every.max(1),
+            unflushed: 0,
+        })
+    }
+
+    pub(crate) fn append(&mut self, update: &PriceUpdate) -> Result<(), TickerError> {
+        let frame = encode_updates(std::slice::from_ref(update), EncodeFormat::Binary);
+        self.writer
+            .write_all(&(frame.len() as u32).to_le_bytes())
+            .and_then(|_| self.writer.write_all(&frame))
+            .map_err(io_error)?;
+        self.unflushed += 1;
+        if self.unflushed >= self.flush_every {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn flush(&mut self) -> Result<(), TickerError> {
+        self.unflushed = 0;
+        self.writer.flush().map_err(io_error)
+    }
+}
+
+// A torn final frame (short length prefix or short body) is dropped; a
+// complete frame that fails to decode means the file is corrupt.
+pub(crate) fn read_recording(path: &str) -> Result<Vec<PriceUpdate>, TickerError> {
+    let data = std::fs::read(path).map_err(io_error)?;
+    let mut updates = Vec::new();
+    let mut frames = complete_frames(&data);
+    while let Some(frame) = next_frame(&mut frames) {
+        updates.extend(decode_updates(frame.to_vec())?);
+    }
+    Ok(updates)
+}
+
+// The prefix of `data` made of whole frames.
+fn complete_frames(data: &[u8]) -> &[u8] {
+    let mut rest = data;
+    while next_frame(&mut rest).is_some() {}
+    &data[..data.len() - rest.len()]
+}
+
+// Splits one whole frame's body off the front of `data`, or leaves `data`
+// alone if what's left is torn.
+fn next_frame<'a>(data: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let prefix = data.get(..4)?;
+    let len = u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+    let frame = data.get(4..4usize.checked_add(len)?)?;
+    *data = &data[4 + len..];
+    Some(frame)
+}
+
+fn io_error(err: std::io::Error) -> TickerError {
+    TickerError::Io {
+        reason: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{TickDirection, UpdateKind};
+
+    fn update(sequence: u64) -> PriceUpdate {
+        PriceUpdate {
+            symbol: "AAPL".to_string(),
+            price: 100.0 + sequence as f64,
+            timestamp_ms: sequence as i64,
+            kind: UpdateKind::Tick,
+            reason: None,
+            direction: TickDirection::Up,
+            prev_price: 99.0 + sequence as f64,
+            sequence,
+        }
+    }
+
+    fn temp_path(name: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("ticker_core_{name}_{}.rec", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    fn record(path: &str, sequences: std::ops::Range<u64>) {
+        let mut recorder = Recorder::open(path, 1).unwrap();
+        for sequence in sequences {
+            recorder.append(&update(sequence)).unwrap();
+        }
+        recorder.flush().unwrap();
+    }
+
+    fn sequences(path: &str) -> Vec<u64> {
+        read_recording(path)
+            .unwrap()
+            .iter()
+            .map(|update| update.sequence)
+            .collect()
+    }
+
+    fn truncate_by(path: &str, bytes: u64) {
+        let file = OpenOptions::new().write(true).open(path).unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len - bytes).unwrap();
+    }
+
+    #[test]
+    fn reopening_after_a_torn_body_keeps_new_frames() {
+        let path = temp_path("torn_body");
+        record(&path, 1..4);
+        truncate_by(&path, 3);
+        assert_eq!(sequences(&path), vec![1, 2]);
+
+        record(&path, 4..12);
+        assert_eq!(
+            sequences(&path),
+            (1..12).filter(|&s| s != 3).collect::<Vec<_>>()
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn reopening_after_a_torn_length_prefix_keeps_new_frames() {
+        let path = temp_path("torn_prefix");
+        record(&path, 1..3);
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(&[9, 0])
+            .unwrap();
+        assert_eq!(sequences(&path), vec![1, 2]);
+
+        record(&path, 3..5);
+        assert_eq!(sequences(&path), vec![1, 2, 3, 4]);
+        let _ = std::fs::remove_file(&path);
+    }
+}
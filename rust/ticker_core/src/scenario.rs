@@ -0,0 +1,128 @@
+use crate::api::{ScenarioStep, TickerError};
+use std::collections::HashSet;
+use std::time::Instant;
+
+pub(crate) struct Scenario {
+    started: Instant,
+    // Sorted by `at_ms`; applied steps are removed.
+    pending: Vec<ScenarioStep>,
+    symbols: HashSet<String>,
+}
+
+impl Scenario {
+    pub(crate) fn new(mut steps: Vec<ScenarioStep>) -> Result<Self, TickerError> {
+        if steps.is_empty() {
+            return Err(invalid("scenario has no steps".to_string()));
+        }
+        for step in &steps {
+            match (step.price, step.delta_pct) {
+                (Some(price), None) if price.is_finite() && price > 0.0 => {}
+                (None, Some(delta_pct)) if delta_pct.is_finite() && delta_pct > -100.0 => {}
+                (Some(_), Some(_)) | (None, None) => {
+                    return Err(invalid(format!(
+                        "step for {} at {}ms needs exactly one of price or delta_pct",
+                        step.symbol, step.at_ms
+                    )));
+                }
+                _ => {
+                    return Err(invalid(format!(
+                        "step for {} at {}ms moves the price to zero or below",
+                        step.symbol, step.at_ms
+                    )));
+                }
+            }
+        }
+        steps.sort_by_key(|step| step.at_ms);
+        Ok(Self {
+            started: Instant::now(),
+            symbols: steps.iter().map(|step| step.symbol.clone()).collect(),
+            pending: steps,
+        })
+    }
+
+    pub(crate) fn symbols(&self) -> &HashSet<String> {
+        &self.symbols
+    }
+
+    pub(crate) fn touches(&self, symbol: &str) -> bool {
+        self.symbols.contains(symbol)
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    // Applies every step for `symbol` that has come due, in order, and
+    // reports whether any did.
+    pub(crate) fn apply_due(&mut self, symbol: &str, price: &mut f64) -> bool {
+        let elapsed_ms = self.elapsed_ms();
+        let mut applied = false;
+        self.pending.retain(|step| {
+            if step.symbol != symbol || step.at_ms > elapsed_ms {
+                return true;
+            }
+            *price = match (step.price, step.delta_pct) {
+                (Some(target), _) => target,
+                (None, Some(delta_pct)) => *price * (1.0 + delta_pct / 100.0),
+                (None, None) => *price,
+            };
+            applied = true;
+            false
+        });
+        applied
+    }
+
+    // A halted symbol doesn't trade, so its due steps lapse instead of all
+    // firing at once on resume.
+    pub(crate) fn skip_due(&mut self, symbol: &str) {
+        let elapsed_ms = self.elapsed_ms();
+        self.pending
+            .retain(|step| step.symbol != symbol || step.at_ms > elapsed_ms);
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+}
+
+fn invalid(reason: String) -> TickerError {
+    TickerError::InvalidScenario { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(at_ms: u64, symbol: &str, price: f64) -> ScenarioStep {
+        ScenarioStep {
+            at_ms,
+            symbol: symbol.to_string(),
+            price: Some(price),
+            delta_pct: None,
+        }
+    }
+
+    #[test]
+    fn skipped_steps_lapse_and_let_the_scenario_finish() {
+        let mut scenario = Scenario::new(vec![
+            step(0, "AAPL", 50.0),
+            step(0, "MSFT", 60.0),
+            step(60_000, "AAPL", 70.0),
+        ])
+        .unwrap();
+        scenario.skip_due("AAPL");
+        let mut price = 100.0;
+        assert!(scenario.apply_due("MSFT", &mut price));
+        assert_eq!(price, 60.0);
+        assert!(!scenario.is_finished());
+
+        // The later step is not due yet, so only the overdue one was dropped.
+        let mut price = 100.0;
+        assert!(!scenario.apply_due("AAPL", &mut price));
+        assert_eq!(price, 100.0);
+
+        let mut halted_only = Scenario::new(vec![step(0, "AAPL", 50.0)]).unwrap();
+        halted_only.skip_due("AAPL");
+        assert!(halted_only.is_finished());
+    }
+}
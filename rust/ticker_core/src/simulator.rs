@@ -0,0 +1,403 @@
+use crate::api::{
+    PriceUpdate, SymbolKind, SymbolMetadata, TickDirection, TickerConfig, TickerError, UpdateKind,
+};
+use crate::engine::lock;
+use crate::pricing::{default_metadata, derived_inverse_metadata, inverse_symbol, PriceModel};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// The engine ticks every 500ms; `step` scales noise against that interval.
+pub(crate) const TICK_INTERVAL_MS: u64 = 500;
+
+// The one implementation of the price walk, shared by the engine's tick loop
+// and `PriceSimulator`. A Vec keeps iteration order stable so seeded runs are
+// reproducible.
+pub(crate) struct SimCore {
+    rng: StdRng,
+    prices: Vec<(String, f64)>,
+}
+
+impl SimCore {
+    pub(crate) fn new(seed: Option<u64>) -> Self {
+        Self {
+            rng: match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            prices: Vec::new(),
+        }
+    }
+
+    // Adding a symbol twice keeps its current price. The starting price is
+    // already on tick, so it reads the same before and after `step(0)`.
+    pub(crate) fn add_symbol(&mut self, metadata: &SymbolMetadata, config: &TickerConfig) {
+        if self.price(&metadata.symbol).is_none() {
+            let model = PriceModel::resolve(metadata, config);
+            let base = initial_price(metadata, &mut self.rng);
+            self.prices
+                .push((metadata.symbol.clone(), model.round(base.max(model.floor))));
+        }
+    }
+
+    pub(crate) fn symbols(&self) -> Vec<String> {
+        self.prices
+            .iter()
+            .map(|(symbol, _)| symbol.clone())
+            .collect()
+    }
+
+    pub(crate) fn price(&self, symbol: &str) -> Option<f64> {
+        self.prices
+            .iter()
+            .find(|(tracked, _)| tracked == symbol)
+            .map(|(_, price)| *price)
+    }
+
+    pub(crate) fn price_mut(&mut self, symbol: &str) -> Option<&mut f64> {
+        self.prices
+            .iter_mut()
+            .find(|(tracked, _)| tracked == symbol)
+            .map(|(_, price)| price)
+    }
+
+    // Moves one symbol by a uniform draw scaled by `noise_scale`, then clamps
+    // and rounds. The draw always happens, so a silenced symbol (scale 0)
+    // doesn't shift other symbols' paths.
+    pub(crate) fn walk(
+        &mut self,
+        symbol: &str,
+        model: &PriceModel,
+        noise_scale: f64,
+    ) -> Option<f64> {
+        let delta = self.rng.gen_range(-model.volatility..=model.volatility);
+        let price = self.price_mut(symbol)?;
+        *price = model.round((*price + delta * noise_scale).max(model.floor));
+        Some(*price)
+    }
+}
+
+pub(crate) fn initial_price(metadata: &SymbolMetadata, rng: &mut StdRng) -> f64 {
+    match metadata.base_price {
+        Some(base) => base,
+        None => {
+            let (low, high) = PriceModel::default_base_range(metadata);
+            rng.gen_range(low..high)
+        }
+    }
+}
+
+// The inverse leg is derived from the rounded direct price so both quotes
+// always agree.
+pub(crate) fn inverse_leg(
+    direct: &SymbolMetadata,
+    price: f64,
+    config: &TickerConfig,
+    inverse_metadata: impl FnOnce(&str) -> SymbolMetadata,
+) -> Option<(String, f64, PriceModel)> {
+    match direct.kind {
+        SymbolKind::Fx {
+            track_inverse: true,
+            ..
+        } => inverse_symbol(&direct.symbol).map(|inverse| {
+            let model = PriceModel::resolve(&inverse_metadata(&inverse), config);
+            let inverse_price = model.round((1.0 / price).max(model.floor));
+            (inverse, inverse_price, model)
+        }),
+        _ => None,
+    }
+}
+
+struct SimulatorState {
+    config: TickerConfig,
+    metadata: HashMap<String, SymbolMetadata>,
+    core: SimCore,
+    inverses: HashMap<String, f64>,
+    clock_ms: i64,
+    next_sequence: u64,
+}
+
+impl SimulatorState {
+    fn symbol_metadata(&self, symbol: &str) -> SymbolMetadata {
+        self.metadata
+            .get(symbol)
+            .cloned()
+            .unwrap_or_else(|| default_metadata(symbol))
+    }
+}
+
+// The price model without threads, runtime, queue, or listeners: prices only
+// move when the host calls `step`. Given the same config, seed, symbols, and
+// sequence of calls, every run produces the same updates. Timestamps are the
+// simulator's own clock, starting at 0.
+#[derive(uniffi::Object)]
+pub struct PriceSimulator {
+    state: Mutex<SimulatorState>,
+}
+
+#[uniffi::export]
+impl PriceSimulator {
+    // `seed` takes precedence over `config.seed`.
+    #[uniffi::constructor]
+    pub fn new(config: TickerConfig, seed: u64) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(SimulatorState {
+                config,
+                metadata: HashMap::new(),
+                core: SimCore::new(Some(seed)),
+                inverses: HashMap::new(),
+                clock_ms: 0,
+                next_sequence: 1,
+            }),
+        })
+    }
+
+    // Symbols step in the order they were added. Re-adding a symbol updates
+    // its metadata but keeps its current price.
+    pub fn add_symbol(&self, metadata: SymbolMetadata) {
+        let mut state = lock(&self.state);
+        let state = &mut *state;
+        state.core.add_symbol(&metadata, &state.config);
+        state.metadata.insert(metadata.symbol.clone(), metadata);
+    }
+
+    // Advances the clock and moves every symbol once. Noise scales with the
+    // square root of `elapsed_ms` relative to the engine's 500ms tick, and
+    // `step(0)` only re-emits prices. With the same seed and symbols, repeated
+    // `step(500)` calls follow a seeded engine's prices tick for tick, as
+    // long as no halt, scripted move, or corporate action touches them.
+    pub fn step(&self, elapsed_ms: u64) -> Vec<PriceUpdate> {
+        let mut state = lock(&self.state);
+        let state = &mut *state;
+        state.clock_ms = state
+            .clock_ms
+            .saturating_add(i64::try_from(elapsed_ms).unwrap_or(i64::MAX));
+        let noise_scale = (elapsed_ms as f64 / TICK_INTERVAL_MS as f64).sqrt();
+
+        let mut updates = Vec::new();
+        for symbol in state.core.symbols() {
+            let metadata = state.symbol_metadata(&symbol);
+            let model = PriceModel::resolve(&metadata, &state.config);
+            let Some(prev_price) = state.core.price(&symbol) else {
+                continue;
+            };
+            let Some(price) = state.core.walk(&symbol, &model, noise_scale) else {
+                continue;
+            };
+            updates.push(tick(&symbol, price, prev_price, state));
+
+            let inverse = inverse_leg(&metadata, price, &state.config, |inverse| {
+                state
+                    .metadata
+                    .get(inverse)
+                    .cloned()
+                    .unwrap_or_else(|| derived_inverse_metadata(inverse, &metadata, price))
+            });
+            if let Some((inverse, inverse_price, _)) = inverse {
+                let prev_inverse = state
+                    .inverses
+                    .insert(inverse.clone(), inverse_price)
+                    .unwrap_or(inverse_price);
+                updates.push(tick(&inverse, inverse_price, prev_inverse, state));
+            }
+        }
+        updates
+    }
+
+    // Takes effect immediately and is rounded to the symbol's tick; the next
+    // `step` walks from it.
+    pub fn set_price(&self, symbol: String, price: f64) -> Result<(), TickerError> {
+        if !price.is_finite() || price <= 0.0 {
+            return Err(TickerError::InvalidPrice { price });
+        }
+        let mut state = lock(&self.state);
+        let model = PriceModel::resolve(&state.symbol_metadata(&symbol), &state.config);
+        let current = state
+            .core
+            .price_mut(&symbol)
+            .ok_or(TickerError::UntrackedSymbol { symbol })?;
+        *current = model.round(price.max(model.floor));
+        Ok(())
+    }
+
+    // Includes inverse legs once they have been stepped.
+    pub fn prices(&self) -> HashMap<String, f64> {
+        let state = lock(&self.state);
+        let mut prices = state.inverses.clone();
+        for symbol in state.core.symbols() {
+            if let Some(price) = state.core.price(&symbol) {
+                prices.insert(symbol, price);
+            }
+        }
+        prices
+    }
+}
+
+fn tick(symbol: &str, price: f64, prev_price: f64, state: &mut SimulatorState) -> PriceUpdate {
+    let sequence = state.next_sequence;
+    state.next_sequence += 1;
+    PriceUpdate {
+        symbol: symbol.to_string(),
+        price,
+        timestamp_ms: state.clock_ms,
+        kind: UpdateKind::Tick,
+        reason: None,
+        direction: TickDirection::between(prev_price, price),
+        prev_price,
+        sequence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fx(symbol: &str, pip_size: f64, base_price: f64) -> SymbolMetadata {
+        SymbolMetadata {
+            symbol: symbol.to_string(),
+            kind: SymbolKind::Fx {
+                pip_size,
+                track_inverse: true,
+            },
+            tick_size: 0.01,
+            base_price: Some(base_price),
+            volatility: None,
+            spread: None,
+            continuous: false,
+        }
+    }
+
+    fn equity(symbol: &str) -> SymbolMetadata {
+        default_metadata(symbol)
+    }
+
+    fn run(seed: u64, steps: &[u64]) -> Vec<(String, u64, i64)> {
+        let simulator = PriceSimulator::new(TickerConfig::default(), seed);
+        simulator.add_symbol(equity("AAPL"));
+        simulator.add_symbol(fx("EURUSD", 0.0001, 1.08));
+        steps
+            .iter()
+            .flat_map(|&elapsed_ms| simulator.step(elapsed_ms))
+            .map(|update| (update.symbol, update.price.to_bits(), update.timestamp_ms))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_and_steps_give_the_same_updates() {
+        let steps = [500, 250, 1_000, 0, 500];
+        assert_eq!(run(42, &steps), run(42, &steps));
+        assert_ne!(run(42, &steps), run(43, &steps));
+    }
+
+    #[test]
+    fn zero_step_reports_without_moving() {
+        let simulator = PriceSimulator::new(TickerConfig::default(), 3);
+        simulator.add_symbol(equity("AAPL"));
+        let before = simulator.prices();
+        let updates = simulator.step(0);
+        assert_eq!(simulator.prices(), before);
+        assert_eq!(updates[0].direction, TickDirection::Unchanged);
+        assert_eq!(updates[0].timestamp_ms, 0);
+    }
+
+    #[test]
+    fn set_price_validates_and_rounds() {
+        let simulator = PriceSimulator::new(TickerConfig::default(), 3);
+        simulator.add_symbol(equity("AAPL"));
+        assert!(matches!(
+            simulator.set_price("AAPL".to_string(), -1.0),
+            Err(TickerError::InvalidPrice { .. })
+        ));
+        assert!(matches!(
+            simulator.set_price("MSFT".to_string(), 10.0),
+            Err(TickerError::UntrackedSymbol { .. })
+        ));
+        simulator.set_price("AAPL".to_string(), 123.456).unwrap();
+        assert_eq!(simulator.prices()["AAPL"], 123.46);
+    }
+
+    #[test]
+    fn walk_stays_on_tick_and_above_the_floor() {
+        let simulator = PriceSimulator::new(TickerConfig::default(), 5);
+        simulator.add_symbol(SymbolMetadata {
+            base_price: Some(0.05),
+            ..equity("PENNY")
+        });
+        for _ in 0..1_000 {
+            let price = simulator.step(500)[0].price;
+            assert!(price >= 0.01);
+            assert_eq!(price, (price * 100.0).round() / 100.0);
+        }
+    }
+
+    #[test]
+    fn steps_of_500ms_follow_a_seeded_engine() {
+        use crate::engine::TickerEngine;
+        use crate::testing::Collect;
+
+        let config = TickerConfig {
+            seed: Some(9),
+            max_ticks: Some(4),
+            ..TickerConfig::default()
+        };
+        let symbols = vec!["AAPL".to_string(), "MSFT".to_string()];
+        let engine = TickerEngine::with_config(config.clone()).unwrap();
+        let collected = Arc::new(Collect::default());
+        engine.start_tracking(symbols.clone(), collected.clone());
+        std::thread::sleep(std::time::Duration::from_millis(3_000));
+
+        let simulator = PriceSimulator::new(config, 9);
+        for symbol in &symbols {
+            simulator.add_symbol(equity(symbol));
+        }
+        let expected: Vec<f64> = (0..4)
+            .flat_map(|_| simulator.step(TICK_INTERVAL_MS))
+            .map(|update| update.price)
+            .collect();
+        let live: Vec<f64> = lock(&collected.0)
+            .iter()
+            .map(|update| update.price)
+            .collect();
+        assert_eq!(live, expected);
+    }
+
+    #[test]
+    fn micro_priced_continuous_symbol_stays_positive_and_on_tick() {
+        let metadata = SymbolMetadata {
+            tick_size: 1e-8,
+            base_price: Some(0.00001234),
+            continuous: true,
+            ..equity("MICRO")
+        };
+        let config = TickerConfig::default();
+        let model = PriceModel::resolve(&metadata, &config);
+        let simulator = PriceSimulator::new(config, 21);
+        simulator.add_symbol(metadata);
+
+        for _ in 0..5_000 {
+            let price = simulator.step(TICK_INTERVAL_MS)[0].price;
+            assert!(price >= 1e-8, "{price} fell below one tick");
+            let ticks = price * 1e8;
+            assert!((ticks - ticks.round()).abs() < 1e-6, "{price} is off-tick");
+            let formatted = model.format(price);
+            assert_eq!(formatted.len(), "0.00001234".len(), "{formatted}");
+            assert_eq!(formatted.parse::<f64>().unwrap(), price);
+        }
+    }
+
+    #[test]
+    fn derived_jpy_inverse_tracks_the_direct_leg() {
+        let simulator = PriceSimulator::new(TickerConfig::default(), 7);
+        simulator.add_symbol(fx("USDJPY", 0.01, 150.0));
+
+        for _ in 0..2_000 {
+            let updates = simulator.step(TICK_INTERVAL_MS);
+            let direct = updates.iter().find(|u| u.symbol == "USDJPY").unwrap();
+            let inverse = updates.iter().find(|u| u.symbol == "JPYUSD").unwrap();
+            assert!(inverse.price > 0.001, "inverse pinned at {}", inverse.price);
+            let product = direct.price * inverse.price;
+            assert!((product - 1.0).abs() < 1e-3, "legs diverged: {product}");
+        }
+    }
+}
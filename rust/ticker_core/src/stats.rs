@@ -0,0 +1,139 @@
+use crate::api::{PriceUpdate, WindowStats};
+use std::collections::VecDeque;
+
+pub(crate) struct StatsWindow {
+    pub(crate) symbol: String,
+    pub(crate) window_ms: u64,
+    // Updates before this instant are ignored, so a reset window starts empty.
+    pub(crate) reset_ms: i64,
+}
+
+impl StatsWindow {
+    // Computed on demand from the symbol's history buffer, which is ordered by
+    // timestamp, so only the updates inside the window are visited.
+    pub(crate) fn compute(
+        &self,
+        history: Option<&VecDeque<PriceUpdate>>,
+        now_ms: i64,
+    ) -> WindowStats {
+        let window_ms = i64::try_from(self.window_ms).unwrap_or(i64::MAX);
+        let window_start = now_ms.saturating_sub(window_ms);
+        let start_ms = window_start.max(self.reset_ms);
+        let covered = match history.and_then(|updates| updates.front()) {
+            Some(oldest) => oldest.timestamp_ms <= window_start,
+            None => false,
+        };
+
+        let mut stats = WindowStats {
+            symbol: self.symbol.clone(),
+            window_ms: self.window_ms,
+            high: 0.0,
+            low: 0.0,
+            mean: 0.0,
+            volatility: 0.0,
+            update_count: 0,
+            complete: covered && self.reset_ms <= window_start,
+        };
+        let Some(updates) = history else {
+            return stats;
+        };
+
+        let first = updates.partition_point(|update| update.timestamp_ms < start_ms);
+        let prices: Vec<f64> = updates.range(first..).map(|update| update.price).collect();
+        if prices.is_empty() {
+            return stats;
+        }
+
+        stats.update_count = prices.len() as u32;
+        stats.high = prices.iter().copied().fold(f64::MIN, f64::max);
+        stats.low = prices.iter().copied().fold(f64::MAX, f64::min);
+        stats.mean = prices.iter().sum::<f64>() / prices.len() as f64;
+        stats.volatility = return_stddev(&prices);
+        stats
+    }
+}
+
+// Population standard deviation of simple tick-to-tick returns.
+fn return_stddev(prices: &[f64]) -> f64 {
+    let returns: Vec<f64> = prices
+        .windows(2)
+        .filter(|pair| pair[0] > 0.0)
+        .map(|pair| pair[1] / pair[0] - 1.0)
+        .collect();
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+    variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{TickDirection, UpdateKind};
+
+    fn history(prices: &[(i64, f64)]) -> VecDeque<PriceUpdate> {
+        prices
+            .iter()
+            .map(|&(timestamp_ms, price)| PriceUpdate {
+                symbol: "AAPL".to_string(),
+                price,
+                timestamp_ms,
+                kind: UpdateKind::Tick,
+                reason: None,
+                direction: TickDirection::Unchanged,
+                prev_price: price,
+                sequence: 0,
+            })
+            .collect()
+    }
+
+    fn window(window_ms: u64, reset_ms: i64) -> StatsWindow {
+        StatsWindow {
+            symbol: "AAPL".to_string(),
+            window_ms,
+            reset_ms,
+        }
+    }
+
+    #[test]
+    fn covered_window_reports_its_updates() {
+        let history = history(&[
+            (1_000, 100.0),
+            (2_000, 102.0),
+            (3_000, 101.0),
+            (4_000, 103.0),
+        ]);
+        let stats = window(2_500, i64::MIN).compute(Some(&history), 4_000);
+        assert_eq!(stats.update_count, 3);
+        assert_eq!((stats.high, stats.low, stats.mean), (103.0, 101.0, 102.0));
+        let returns: [f64; 2] = [101.0 / 102.0 - 1.0, 103.0 / 101.0 - 1.0];
+        let expected = (returns[0] - returns[1]).abs() / 2.0;
+        assert!(
+            (stats.volatility - expected).abs() < 1e-12,
+            "{}",
+            stats.volatility
+        );
+        assert!(stats.complete);
+    }
+
+    #[test]
+    fn windows_reaching_past_the_history_or_reset_are_incomplete() {
+        let history = history(&[(1_000, 100.0), (2_000, 102.0), (3_000, 101.0)]);
+        for window_ms in [10_000, u64::MAX] {
+            let stats = window(window_ms, i64::MIN).compute(Some(&history), 3_000);
+            assert_eq!(stats.update_count, 3, "window {window_ms}");
+            assert!(!stats.complete, "window {window_ms}");
+        }
+
+        let reset = window(2_500, 2_500).compute(Some(&history), 3_000);
+        assert_eq!((reset.update_count, reset.mean), (1, 101.0));
+        assert_eq!(reset.volatility, 0.0);
+        assert!(!reset.complete);
+
+        let empty = window(2_500, i64::MIN).compute(None, 3_000);
+        assert_eq!(empty.update_count, 0);
+        assert!(!empty.complete);
+    }
+}
@@ -0,0 +1,23 @@
+use crate::api::{EngineEvent, EventListener, PriceListener, PriceUpdate};
+use crate::engine::lock;
+use std::sync::Mutex;
+
+// Keeps every price update it is given, in delivery order.
+#[derive(Default)]
+pub(crate) struct Collect(pub(crate) Mutex<Vec<PriceUpdate>>);
+
+impl PriceListener for Collect {
+    fn on_price(&self, update: PriceUpdate) {
+        lock(&self.0).push(update);
+    }
+}
+
+// Keeps every engine event it is given, in delivery order.
+#[derive(Default)]
+pub(crate) struct EventLog(pub(crate) Mutex<Vec<EngineEvent>>);
+
+impl EventListener for EventLog {
+    fn on_event(&self, event: EngineEvent) {
+        lock(&self.0).push(event);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::api::{Fill, OrderBook, OrderSide, Position};
+use std::collections::HashMap;
+
+pub(crate) struct Order {
+    pub(crate) id: u64,
+    pub(crate) symbol: String,
+    pub(crate) side: OrderSide,
+    pub(crate) quantity: f64,
+    pub(crate) limit_price: Option<f64>,
+}
+
+impl Order {
+    // Orders take the touch on the next book, so they pay half the spread:
+    // buys fill at the best ask and sells at the best bid. A limit order
+    // fills once the touch reaches it, at the better of limit and touch.
+    fn fill_price(&self, price: f64, book: &OrderBook) -> Option<f64> {
+        let touch = match self.side {
+            OrderSide::Buy => book.asks.first(),
+            OrderSide::Sell => book.bids.first(),
+        }
+        .map_or(price, |level| level.price);
+        match (self.limit_price, self.side) {
+            (None, _) => Some(touch),
+            (Some(limit), OrderSide::Buy) => (touch <= limit).then_some(touch.min(limit)),
+            (Some(limit), OrderSide::Sell) => (touch >= limit).then_some(touch.max(limit)),
+        }
+    }
+}
+
+// Removes and returns fills for every open order on `book.symbol` that
+// executes against this tick.
+pub(crate) fn match_orders(
+    orders: &mut Vec<Order>,
+    price: f64,
+    book: &OrderBook,
+    timestamp_ms: i64,
+) -> Vec<Fill> {
+    let mut fills = Vec::new();
+    orders.retain(|order| {
+        if order.symbol != book.symbol {
+            return true;
+        }
+        match order.fill_price(price, book) {
+            Some(fill_price) => {
+                fills.push(Fill {
+                    order_id: order.id,
+                    symbol: order.symbol.clone(),
+                    side: order.side,
+                    quantity: order.quantity,
+                    price: fill_price,
+                    timestamp_ms,
+                });
+                false
+            }
+            None => true,
+        }
+    });
+    fills
+}
+
+// Resting orders on `symbol` keep their place relative to the market: a 2:1
+// split halves limit prices and doubles quantities.
+pub(crate) fn apply_split_to_orders(orders: &mut [Order], symbol: &str, ratio: f64) {
+    for order in orders.iter_mut().filter(|order| order.symbol == symbol) {
+        order.quantity *= ratio;
+        order.limit_price = order.limit_price.map(|limit| limit / ratio);
+    }
+}
+
+#[derive(Default)]
+struct Holding {
+    quantity: f64,
+    average_price: f64,
+    realized_pnl: f64,
+}
+
+#[derive(Default)]
+pub(crate) struct Portfolio {
+    holdings: HashMap<String, Holding>,
+}
+
+impl Portfolio {
+    pub(crate) fn apply_fill(&mut self, fill: &Fill) {
+        let holding = self.holdings.entry(fill.symbol.clone()).or_default();
+        let signed = match fill.side {
+            OrderSide::Buy => fill.quantity,
+            OrderSide::Sell => -fill.quantity,
+        };
+
+        if holding.quantity == 0.0 || holding.quantity.signum() == signed.signum() {
+            let total = holding.quantity.abs() + signed.abs();
+            holding.average_price = (holding.average_price * holding.quantity.abs()
+                + fill.price * signed.abs())
+                / total;
+            holding.quantity += signed;
+            return;
+        }
+
+        let closing = signed.abs().min(holding.quantity.abs());
+        holding.realized_pnl +=
+            closing * (fill.price - holding.average_price) * holding.quantity.signum();
+        holding.quantity += signed;
+        if holding.quantity.abs() < f64::EPSILON {
+            holding.quantity = 0.0;
+            holding.average_price = 0.0;
+        } else if holding.quantity.signum() == signed.signum() {
+            // The fill flipped the position; the remainder opened at the fill price.
+            holding.average_price = fill.price;
+        }
+    }
+
+    // A 2:1 split doubles the share count and halves the cost basis.
+    pub(crate) fn apply_split(&mut self, symbol: &str, ratio: f64) {
+        if let Some(holding) = self.holdings.get_mut(symbol) {
+            holding.quantity *= ratio;
+            holding.average_price /= ratio;
+        }
+    }
+
+    // Shorts pay the dividend, so the signed quantity does the right thing.
+    pub(crate) fn apply_dividend(&mut self, symbol: &str, amount: f64) {
+        if let Some(holding) = self.holdings.get_mut(symbol) {
+            holding.realized_pnl += holding.quantity * amount;
+        }
+    }
+
+    pub(crate) fn positions(&self, marks: &HashMap<String, f64>) -> Vec<Position> {
+        let mut positions: Vec<Position> = self
+            .holdings
+            .iter()
+            .map(|(symbol, holding)| {
+                let unrealized_pnl = match marks.get(symbol) {
+                    Some(mark) if holding.quantity != 0.0 => {
+                        (mark - holding.average_price) * holding.quantity
+                    }
+                    _ => 0.0,
+                };
+                Position {
+                    symbol: symbol.clone(),
+                    quantity: holding.quantity,
+                    average_price: holding.average_price,
+                    realized_pnl: holding.realized_pnl,
+                    unrealized_pnl,
+                }
+            })
+            .collect();
+        positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::BookLevel;
+
+    fn book(bid: f64, ask: f64) -> OrderBook {
+        let level = |price| BookLevel { price, size: 100.0 };
+        OrderBook {
+            symbol: "AAPL".to_string(),
+            bids: vec![level(bid)],
+            asks: vec![level(ask)],
+            timestamp_ms: 0,
+        }
+    }
+
+    fn order(side: OrderSide, limit_price: Option<f64>) -> Order {
+        Order {
+            id: 1,
+            symbol: "AAPL".to_string(),
+            side,
+            quantity: 10.0,
+            limit_price,
+        }
+    }
+
+    fn fill(side: OrderSide, quantity: f64, price: f64) -> Fill {
+        Fill {
+            order_id: 1,
+            symbol: "AAPL".to_string(),
+            side,
+            quantity,
+            price,
+            timestamp_ms: 0,
+        }
+    }
+
+    fn position(portfolio: &Portfolio, mark: f64) -> Position {
+        let marks = HashMap::from([("AAPL".to_string(), mark)]);
+        portfolio.positions(&marks).remove(0)
+    }
+
+    #[test]
+    fn orders_fill_on_the_taking_side_of_the_spread() {
+        let book = book(99.0, 101.0);
+        let cases = [
+            (order(OrderSide::Buy, None), Some(101.0)),
+            (order(OrderSide::Sell, None), Some(99.0)),
+            // Marketable limits pay the touch, never better than a market order.
+            (order(OrderSide::Buy, Some(10_000.0)), Some(101.0)),
+            (order(OrderSide::Sell, Some(1.0)), Some(99.0)),
+            // Between the touch and the mid: not yet reached.
+            (order(OrderSide::Buy, Some(100.5)), None),
+            (order(OrderSide::Sell, Some(99.5)), None),
+            (order(OrderSide::Buy, Some(101.0)), Some(101.0)),
+            (order(OrderSide::Sell, Some(99.0)), Some(99.0)),
+        ];
+        for (order, expected) in cases {
+            assert_eq!(
+                order.fill_price(100.0, &book),
+                expected,
+                "{:?} limit {:?}",
+                order.side,
+                order.limit_price
+            );
+        }
+    }
+
+    #[test]
+    fn match_orders_only_fills_this_symbol() {
+        let mut orders = vec![
+            order(OrderSide::Buy, None),
+            Order {
+                symbol: "MSFT".to_string(),
+                ..order(OrderSide::Buy, None)
+            },
+            order(OrderSide::Buy, Some(50.0)),
+        ];
+        let fills = match_orders(&mut orders, 100.0, &book(99.0, 101.0), 7);
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].price, fills[0].timestamp_ms), (101.0, 7));
+        assert_eq!(orders.len(), 2);
+    }
+
+    #[test]
+    fn fills_average_in_then_realize_on_close_and_flip() {
+        let mut portfolio = Portfolio::default();
+        portfolio.apply_fill(&fill(OrderSide::Buy, 10.0, 100.0));
+        portfolio.apply_fill(&fill(OrderSide::Buy, 30.0, 120.0));
+        let opened = position(&portfolio, 130.0);
+        assert_eq!((opened.quantity, opened.average_price), (40.0, 115.0));
+        assert_eq!(opened.unrealized_pnl, 600.0);
+
+        // Partial close keeps the average and books the difference.
+        portfolio.apply_fill(&fill(OrderSide::Sell, 10.0, 125.0));
+        let reduced = position(&portfolio, 125.0);
+        assert_eq!((reduced.quantity, reduced.average_price), (30.0, 115.0));
+        assert_eq!(reduced.realized_pnl, 100.0);
+
+        // Selling through flat closes 30 and opens a 20 short at the fill.
+        portfolio.apply_fill(&fill(OrderSide::Sell, 50.0, 110.0));
+        let flipped = position(&portfolio, 100.0);
+        assert_eq!((flipped.quantity, flipped.average_price), (-20.0, 110.0));
+        assert_eq!(flipped.realized_pnl, 100.0 - 150.0);
+        assert_eq!(flipped.unrealized_pnl, 200.0);
+
+        portfolio.apply_fill(&fill(OrderSide::Buy, 20.0, 105.0));
+        let flat = position(&portfolio, 100.0);
+        assert_eq!((flat.quantity, flat.average_price), (0.0, 0.0));
+        assert_eq!(flat.realized_pnl, -50.0 + 100.0);
+        assert_eq!(flat.unrealized_pnl, 0.0);
+    }
+
+    #[test]
+    fn split_scales_positions_and_resting_orders() {
+        let mut portfolio = Portfolio::default();
+        portfolio.apply_fill(&fill(OrderSide::Buy, 10.0, 100.0));
+        portfolio.apply_split("AAPL", 2.0);
+        let split = position(&portfolio, 50.0);
+        assert_eq!((split.quantity, split.average_price), (20.0, 50.0));
+        assert_eq!(split.unrealized_pnl, 0.0);
+
+        let mut orders = vec![
+            order(OrderSide::Buy, Some(98.0)),
+            order(OrderSide::Sell, None),
+            Order {
+                symbol: "MSFT".to_string(),
+                ..order(OrderSide::Buy, Some(98.0))
+            },
+        ];
+        apply_split_to_orders(&mut orders, "AAPL", 2.0);
+        let scaled: Vec<(f64, Option<f64>)> = orders
+            .iter()
+            .map(|order| (order.quantity, order.limit_price))
+            .collect();
+        assert_eq!(
+            scaled,
+            vec![(20.0, Some(49.0)), (20.0, None), (10.0, Some(98.0))]
+        );
+        // The pre-split bid still rests below the post-split market.
+        let fills = match_orders(&mut orders, 50.0, &book(49.5, 50.5), 0);
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].side, OrderSide::Sell);
+        assert_eq!(orders.len(), 2);
+    }
+
+    #[test]
+    fn dividends_pay_longs_and_charge_shorts() {
+        let mut long = Portfolio::default();
+        long.apply_fill(&fill(OrderSide::Buy, 10.0, 100.0));
+        long.apply_dividend("AAPL", 0.5);
+        let paid = position(&long, 99.5);
+        assert_eq!((paid.realized_pnl, paid.average_price), (5.0, 100.0));
+
+        let mut short = Portfolio::default();
+        short.apply_fill(&fill(OrderSide::Sell, 10.0, 100.0));
+        short.apply_dividend("AAPL", 0.5);
+        assert_eq!(position(&short, 99.5).realized_pnl, -5.0);
+
+        // Dividends on other symbols leave the position alone.
+        short.apply_dividend("MSFT", 1.0);
+        assert_eq!(position(&short, 99.5).realized_pnl, -5.0);
+    }
+}